@@ -12,6 +12,16 @@ fn main() {
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
+    // `run <graph.json>` evaluates without opening the window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("run") {
+        if let Err(err) = node::cli::run(&args[1..]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let options = eframe::NativeOptions {
         initial_window_size: Some(egui::vec2(320.0, 240.0)),
         ..Default::default()
@@ -19,7 +29,14 @@ fn main() {
     eframe::run_native(
         "Secret Orchestrator",
         options,
-        Box::new(|_cc| Box::new(Orchestrator::default())),
+        Box::new(|_cc| {
+            #[cfg(feature = "persistence")]
+            return Box::new(Orchestrator {
+                graph_state: OrchestratorNodeGraph::new(_cc),
+            });
+            #[cfg(not(feature = "persistence"))]
+            Box::new(Orchestrator::default())
+        }),
     )
 }
 
@@ -39,4 +56,9 @@ impl eframe::App for Orchestrator {
     fn update(&mut self, ctx: &Context, frame: &mut Frame) {
         self.graph_state.update(ctx, frame)
     }
+
+    #[cfg(feature = "persistence")]
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::App::save(&mut self.graph_state, storage)
+    }
 }
@@ -0,0 +1,149 @@
+use anyhow::anyhow;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// Chain the effectful nodes run their txs against
+pub trait ChainClient {
+    /// Signs the tx as the client's account
+    fn sign(&self, tx: Value) -> anyhow::Result<SignedTx>;
+    /// Runs the signed tx against the current chain state without broadcasting it
+    fn simulate(&self, tx: &SignedTx) -> anyhow::Result<Simulation>;
+}
+
+pub struct SignedTx {
+    pub body: Value,
+    pub signature: String,
+    /// Hex encoded SHA-256 of the signed tx, the hash it would be broadcast under
+    pub hash: String,
+}
+
+impl SignedTx {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "body": self.body,
+            "signature": self.signature,
+            "hash": self.hash,
+        })
+    }
+}
+
+/// What the tx would do if it was broadcast
+pub struct Simulation {
+    pub gas_used: u64,
+    /// Response of the msg, e.g. the address an instantiate would get
+    pub result: Value,
+}
+
+/// Gas every tx pays before running its msg
+const BASE_GAS: u64 = 50_000;
+/// Gas per byte of msg, or of wasm when storing code
+const GAS_PER_BYTE: u64 = 10;
+const MOCK_KEY: &str = "mock";
+
+/// Offline stand-in for a node, gas grows with the size of the tx and results are derived
+/// from its content so the same tx always simulates the same way
+#[derive(Default)]
+pub struct MockChain;
+
+impl ChainClient for MockChain {
+    fn sign(&self, tx: Value) -> anyhow::Result<SignedTx> {
+        let bytes = serde_json::to_vec(&tx)?;
+        let signature = hex::encode(Sha256::digest([MOCK_KEY.as_bytes(), &bytes].concat()));
+        let hash = hex::encode(Sha256::digest(
+            [bytes, signature.clone().into_bytes()].concat(),
+        ))
+        .to_uppercase();
+        Ok(SignedTx {
+            body: tx,
+            signature,
+            hash,
+        })
+    }
+
+    fn simulate(&self, tx: &SignedTx) -> anyhow::Result<Simulation> {
+        let expected = hex::encode(Sha256::digest(
+            [MOCK_KEY.as_bytes(), &serde_json::to_vec(&tx.body)?].concat(),
+        ));
+        if tx.signature != expected {
+            anyhow::bail!("Invalid signature");
+        }
+
+        let (kind, msg) = tx
+            .body
+            .as_object()
+            .and_then(|body| {
+                body.iter()
+                    .find(|(key, _)| key.as_str() != "fee")
+                    .map(|(kind, msg)| (kind.as_str(), msg))
+            })
+            .ok_or_else(|| anyhow!("Tx has no msg"))?;
+        let size = serde_json::to_vec(msg)?.len() as u64;
+
+        let (gas, result) = match kind {
+            "store_code" => {
+                let wasm = msg["size"].as_u64().unwrap_or(size);
+                let code_id = u64::from_str_radix(&tx.hash[..4], 16)? + 1;
+                (wasm * GAS_PER_BYTE, json!({ "code_id": code_id }))
+            }
+            "instantiate_contract" => {
+                let address = format!("secret1{}", &tx.hash[..38].to_lowercase());
+                (size * GAS_PER_BYTE, json!({ "address": address }))
+            }
+            "execute_contract" => {
+                if !msg["contract"].is_string() {
+                    anyhow::bail!("Cannot execute a contract without an address");
+                }
+                (size * GAS_PER_BYTE, json!({ "data": null }))
+            }
+            kind => anyhow::bail!("Unknown msg {}", kind),
+        };
+
+        Ok(Simulation {
+            gas_used: BASE_GAS + gas,
+            result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulating_is_deterministic() {
+        let chain = MockChain;
+        let tx =
+            json!({ "instantiate_contract": { "code_id": 1, "label": "token-v1", "msg": {} } });
+        let a = chain.simulate(&chain.sign(tx.clone()).unwrap()).unwrap();
+        let b = chain.simulate(&chain.sign(tx).unwrap()).unwrap();
+        assert_eq!(a.gas_used, b.gas_used);
+        assert_eq!(a.result, b.result);
+        assert!(a.result["address"].as_str().unwrap().starts_with("secret1"));
+    }
+
+    #[test]
+    fn gas_grows_with_the_tx() {
+        let chain = MockChain;
+        let gas = |size: u64| {
+            let tx = json!({ "store_code": { "size": size } });
+            chain.simulate(&chain.sign(tx).unwrap()).unwrap().gas_used
+        };
+        assert_eq!(gas(0), BASE_GAS);
+        assert!(gas(1000) > gas(10));
+    }
+
+    #[test]
+    fn tampered_or_invalid_txs_fail() {
+        let chain = MockChain;
+        let mut signed = chain
+            .sign(json!({ "execute_contract": { "contract": "secret1abc", "msg": {} } }))
+            .unwrap();
+        assert!(chain.simulate(&signed).is_ok());
+        signed.body["execute_contract"]["contract"] = json!("secret1xyz");
+        assert!(chain.simulate(&signed).is_err());
+
+        let null = json!({ "execute_contract": { "contract": null, "msg": {} } });
+        assert!(chain.simulate(&chain.sign(null).unwrap()).is_err());
+        assert!(chain.simulate(&chain.sign(json!({})).unwrap()).is_err());
+    }
+}
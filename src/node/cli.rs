@@ -0,0 +1,93 @@
+use crate::node::debugger::Debugger;
+use crate::node::fragment::Fragment;
+use crate::node::manifest::Manifest;
+use crate::node::{GraphState, NodeGraph};
+use anyhow::anyhow;
+use egui_node_graph::NodeId;
+use std::path::Path;
+
+const USAGE: &str = "Usage: secret-orchestrator run <graph.json> [--node <label>] \
+    [--deployment <file>] [--dry-run] [--transcript <path>]";
+
+/// Runs a graph without the window and exports its transcript, for CI and scripts.
+/// The graph file holds nodes copied in the editor, the same json Copy puts on the clipboard.
+pub fn run(args: &[String]) -> anyhow::Result<()> {
+    let mut graph_path = None;
+    let mut node = None;
+    let mut deployment = None;
+    let mut dry_run = false;
+    let mut transcript = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| anyhow!("{} needs a value\n{}", arg, USAGE))
+        };
+        match arg.as_str() {
+            "--node" => node = Some(value()?),
+            "--deployment" => deployment = Some(value()?),
+            "--transcript" => transcript = Some(value()?),
+            "--dry-run" => dry_run = true,
+            path if graph_path.is_none() && !path.starts_with("--") => {
+                graph_path = Some(path.to_string())
+            }
+            arg => anyhow::bail!("Unexpected argument {}\n{}", arg, USAGE),
+        }
+    }
+
+    let graph_path = graph_path.ok_or_else(|| anyhow!(USAGE))?;
+    let text = std::fs::read_to_string(&graph_path)
+        .map_err(|err| anyhow!("Could not read {}: {}", graph_path, err))?;
+    let fragment: Fragment = serde_json::from_str(&text)
+        .map_err(|err| anyhow!("{} is not a copied graph: {}", graph_path, err))?;
+
+    let mut graph = NodeGraph::new();
+    let ids = fragment.insert(&mut graph, &mut GraphState::default());
+    let node_id = target(&graph, &ids, node.as_deref())?;
+    let deployment = match deployment {
+        Some(path) => Some(Manifest::read(Path::new(&path))?),
+        None => None,
+    };
+
+    let debugger = Debugger::run_to_end(&graph, node_id, deployment, dry_run);
+    let path = transcript.unwrap_or_else(|| debugger.transcript.file_name());
+    debugger.transcript.export(Path::new(&path))?;
+
+    match debugger.error {
+        Some(err) => Err(anyhow!("{} failed: {}", graph[node_id].label, err)),
+        None => Ok(()),
+    }
+}
+
+/// The node with the given label, or the only one nothing else depends on
+fn target(graph: &NodeGraph, ids: &[NodeId], label: Option<&str>) -> anyhow::Result<NodeId> {
+    if let Some(label) = label {
+        return ids
+            .iter()
+            .copied()
+            .find(|id| graph[*id].label == label)
+            .ok_or_else(|| anyhow!("No node is labeled {}", label));
+    }
+
+    let last: Vec<NodeId> = ids
+        .iter()
+        .copied()
+        .filter(|id| {
+            !graph
+                .iter_connections()
+                .any(|(_, output)| graph[output].node == *id)
+        })
+        .collect();
+    match last.as_slice() {
+        [id] => Ok(*id),
+        _ => {
+            let labels: Vec<_> = last.iter().map(|id| graph[*id].label.as_str()).collect();
+            anyhow::bail!(
+                "Pick the node to run with --node, one of {}",
+                labels.join(", ")
+            )
+        }
+    }
+}
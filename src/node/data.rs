@@ -1,3 +1,5 @@
+use crate::node::format::TEMPLATE_INPUT;
+use crate::node::template::Template;
 use crate::node::{GraphState, NodeState, Response};
 use anyhow::anyhow;
 use eframe::egui;
@@ -5,64 +7,215 @@ use eframe::egui::{Checkbox, ComboBox, TextEdit, Ui};
 use egui_node_graph::{DataTypeTrait, NodeId, WidgetValueTrait};
 use serde_json::{json, Number, Value};
 use std::borrow::Cow;
+use std::hash::Hash;
+use std::str::FromStr;
 
 /// Determines the communication ranges for the types
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
-#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Eq, Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub enum DataType {
     #[default]
     Bool,
     Number,
     String,
-    Array,
+    /// Array of the given element type
+    Array(Box<DataType>),
     Json,
+    /// Raw bytes, base64 encoded when turned into json like CosmWasm's Binary
+    Binary,
+    /// Native token amount, lists of coins are arrays of coins
+    Coin,
+    /// Same as the wrapped type but may also be null
+    Optional(Box<DataType>),
 }
 
 impl DataType {
-    pub fn combo_box(&mut self, ui: &mut Ui) {
-        ComboBox::from_label("")
-            .selected_text(format!("{:?}", self))
+    pub fn combo_box(&mut self, ui: &mut Ui, id_source: impl Hash) {
+        self.nested_combo_box(ui, egui::Id::new(id_source));
+    }
+
+    /// Array element types get their own combo box
+    fn nested_combo_box(&mut self, ui: &mut Ui, id: egui::Id) {
+        let mut optional = self.is_optional();
+        let mut data = self.inner().clone();
+
+        ComboBox::from_id_source(id)
+            .selected_text(data.name())
             .show_ui(ui, |ui| {
-                ui.selectable_value(self, Self::Bool, "bool");
-                ui.selectable_value(self, Self::Number, "number");
-                ui.selectable_value(self, Self::String, "string");
-                ui.selectable_value(self, Self::Array, "array");
-                ui.selectable_value(self, Self::Json, "json");
+                ui.selectable_value(&mut data, Self::Bool, "bool");
+                ui.selectable_value(&mut data, Self::Number, "number");
+                ui.selectable_value(&mut data, Self::String, "string");
+                // Keep the element type if it already was an array
+                let is_array = matches!(data, Self::Array(_));
+                if ui.selectable_label(is_array, "array").clicked() && !is_array {
+                    data = Self::Array(Box::new(Self::Bool));
+                }
+                ui.selectable_value(&mut data, Self::Json, "json");
+                ui.selectable_value(&mut data, Self::Binary, "binary");
+                ui.selectable_value(&mut data, Self::Coin, "coin");
             });
+        ui.checkbox(&mut optional, "optional");
+
+        if let Self::Array(element) = &mut data {
+            ui.label("of");
+            element.nested_combo_box(ui, id.with("element"));
+        }
+
+        *self = if optional {
+            Self::Optional(Box::new(data))
+        } else {
+            data
+        };
+    }
+
+    pub fn is_optional(&self) -> bool {
+        matches!(self, DataType::Optional(_))
+    }
+
+    /// The type without its optional wrapper
+    pub fn inner(&self) -> &DataType {
+        match self {
+            DataType::Optional(data) => data.inner(),
+            data => data,
+        }
+    }
+
+    /// Exact comparison, `==` ignores the optional wrapper so values can be connected to it
+    pub fn is(&self, other: &DataType) -> bool {
+        match (self, other) {
+            (DataType::Array(a), DataType::Array(b))
+            | (DataType::Optional(a), DataType::Optional(b)) => a.is(b),
+            (DataType::Array(_) | DataType::Optional(_), _) => false,
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+
+    /// Inline value of new inputs, optional ones keep an editor for the wrapped type
+    pub fn input_value(&self) -> ValueType {
+        match self {
+            DataType::Optional(data) => ValueType::Optional {
+                value: Box::new(data.input_value()),
+                set: false,
+            },
+            data => data.default_value(),
+        }
+    }
+
+    /// Empty value for outputs the debugger skips
+    pub fn default_value(&self) -> ValueType {
+        match self {
+            DataType::Bool => ValueType::Bool(false),
+            DataType::Number => ValueType::Number(Number::from(0)),
+            DataType::String => ValueType::String("".to_string()),
+            DataType::Array(_) => ValueType::Array(vec![]),
+            DataType::Json => ValueType::Json(Value::default()),
+            DataType::Binary => ValueType::Binary(vec![]),
+            DataType::Coin => ValueType::Coin(Coin::default()),
+            DataType::Optional(_) => ValueType::Null,
+        }
+    }
+}
+
+/// Connections are only allowed between equal types, so `T` outputs can connect to
+/// `Optional(T)` inputs. The other way around errors once the value is actually null.
+impl PartialEq for DataType {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner().is(other.inner())
     }
 }
 
 /// Implements the Node intractable points color
 impl DataTypeTrait<GraphState> for DataType {
-    fn data_type_color(&self, _user_state: &mut GraphState) -> egui::Color32 {
+    fn data_type_color(&self, user_state: &mut GraphState) -> egui::Color32 {
         match self {
             DataType::Bool => egui::Color32::from_rgb(255, 51, 255),
             DataType::Number => egui::Color32::from_rgb(51, 51, 255),
             DataType::String => egui::Color32::from_rgb(51, 153, 255),
-            DataType::Array => egui::Color32::from_rgb(51, 255, 255),
+            DataType::Array(_) => egui::Color32::from_rgb(51, 255, 255),
             DataType::Json => egui::Color32::from_rgb(255, 255, 51),
+            DataType::Binary => egui::Color32::from_rgb(255, 153, 51),
+            DataType::Coin => egui::Color32::from_rgb(51, 255, 102),
+            DataType::Optional(data) => data.data_type_color(user_state).linear_multiply(0.6),
         }
     }
 
     fn name(&self) -> Cow<str> {
-        Cow::Borrowed(match self {
-            DataType::Bool => "boolean",
-            DataType::Number => "number",
-            DataType::String => "string",
-            DataType::Array => "array",
-            DataType::Json => "json",
-        })
+        match self {
+            DataType::Bool => Cow::Borrowed("boolean"),
+            DataType::Number => Cow::Borrowed("number"),
+            DataType::String => Cow::Borrowed("string"),
+            DataType::Array(data) => Cow::Owned(format!("array of {}", data.name())),
+            DataType::Json => Cow::Borrowed("json"),
+            DataType::Binary => Cow::Borrowed("binary"),
+            DataType::Coin => Cow::Borrowed("coin"),
+            DataType::Optional(data) => Cow::Owned(format!("optional {}", data.name())),
+        }
     }
 }
 
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ValueType {
+    Null,
     Bool(bool),
     Number(Number),
     String(String),
     Array(Vec<Value>),
     Json(Value),
+    Binary(Vec<u8>),
+    Coin(Coin),
+    /// Inline value of an optional input, the wrapped value is kept while it's unset
+    Optional {
+        value: Box<ValueType>,
+        set: bool,
+    },
+}
+
+/// Same as cosmwasm's Coin, the amount is a Uint128 so it's kept as a string
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Coin {
+    pub amount: String,
+    pub denom: String,
+}
+
+impl Default for Coin {
+    fn default() -> Self {
+        Self {
+            amount: "0".to_string(),
+            denom: "uscrt".to_string(),
+        }
+    }
+}
+
+impl Coin {
+    pub fn new(amount: String, denom: String) -> anyhow::Result<Self> {
+        amount
+            .parse::<u128>()
+            .map_err(|_| anyhow!("{} is not a valid Uint128 amount", amount))?;
+        if denom.is_empty() {
+            anyhow::bail!("Coin denom cannot be empty");
+        }
+        Ok(Self { amount, denom })
+    }
+
+    pub fn from_json(value: &Value) -> anyhow::Result<Self> {
+        let amount = match &value["amount"] {
+            Value::String(amount) => amount.clone(),
+            Value::Number(amount) => amount.to_string(),
+            _ => anyhow::bail!("Coin is missing its amount"),
+        };
+        let denom = value["denom"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Coin is missing its denom"))?;
+        Self::new(amount, denom.to_string())
+    }
+
+    /// Reads the `1000uscrt` format
+    pub fn parse(coin: &str) -> anyhow::Result<Self> {
+        let coin = coin.trim();
+        let split = coin
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| anyhow!("Coin {} is missing its denom", coin))?;
+        Self::new(coin[..split].to_string(), coin[split..].to_string())
+    }
 }
 
 impl WidgetValueTrait for ValueType {
@@ -83,20 +236,20 @@ impl WidgetValueTrait for ValueType {
 
         ui.horizontal(|ui| {
             ui.label(param_name);
-            match self {
-                ValueType::Bool(value) => {
-                    ui.add(Checkbox::new(value, ""));
-                }
-                ValueType::Number(_) => {}
-                ValueType::String(value) => {
-                    ui.add(TextEdit::singleline(value));
-                }
-                ValueType::Array(_value) => {}
-                ValueType::Json(_value) => {}
+            let changed = self.editor(ui, egui::Id::new(("value_editor", node_id, param_name)));
+            // Placeholders in a string template are its inputs
+            if changed
+                && node_data.template == Template::FormatString
+                && param_name == TEMPLATE_INPUT
+            {
+                res.push(Response::UpdatePlaceholders(node_id));
             }
 
             if let Some(node) = user_state.editing_node {
-                if node == node_id && node_data.template.is_json() {
+                // The manifest file is not a deployment entry
+                let is_fixed =
+                    node_data.template == Template::DeploymentOutput && param_name == "file";
+                if node == node_id && node_data.template.is_json() && !is_fixed {
                     if ui.button("Remove").clicked() {
                         user_state.json_name = param_name.to_string();
                         res.push(Response::RemoveParam(node_id));
@@ -109,20 +262,330 @@ impl WidgetValueTrait for ValueType {
     }
 }
 
+impl ValueType {
+    /// Inline editor for the value, returns whether it changed
+    fn editor(&mut self, ui: &mut Ui, id: egui::Id) -> bool {
+        match self {
+            ValueType::Null => {
+                ui.weak("null");
+                false
+            }
+            ValueType::Bool(value) => ui.add(Checkbox::new(value, "")).changed(),
+            ValueType::Number(value) => match number_editor(ui, id, value) {
+                Some(number) => {
+                    *value = number;
+                    true
+                }
+                None => false,
+            },
+            ValueType::String(value) => ui.add(TextEdit::singleline(value)).changed(),
+            ValueType::Array(value) => {
+                let current = Value::Array(value.clone());
+                match json_editor(ui, id, &current, true) {
+                    Some(Value::Array(array)) => {
+                        *value = array;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            ValueType::Json(value) => match json_editor(ui, id, value, false) {
+                Some(json) => {
+                    *value = json;
+                    true
+                }
+                None => false,
+            },
+            ValueType::Binary(value) => {
+                ui.weak(format!("{} bytes", value.len()));
+                false
+            }
+            ValueType::Coin(coin) => {
+                let amount = ui.add(TextEdit::singleline(&mut coin.amount).desired_width(80.0));
+                let denom = ui.add(TextEdit::singleline(&mut coin.denom).desired_width(60.0));
+                amount.changed() || denom.changed()
+            }
+            ValueType::Optional { value, set } => {
+                let mut changed = ui.checkbox(set, "").on_hover_text("Set").changed();
+                if *set {
+                    changed |= value.editor(ui, id.with("optional"));
+                } else {
+                    ui.weak("null");
+                }
+                changed
+            }
+        }
+    }
+
+    /// The value an input receives, unset optional inputs are null
+    pub fn resolved(self) -> ValueType {
+        match self {
+            ValueType::Optional { value, set: true } => value.resolved(),
+            ValueType::Optional { set: false, .. } => ValueType::Null,
+            value => value,
+        }
+    }
+}
+
+/// Number field that keeps the typed text while it doesn't parse, returns the new value once it does
+fn number_editor(ui: &mut Ui, id: egui::Id, value: &Number) -> Option<Number> {
+    let mut text = ui
+        .data()
+        .get_temp::<(String, Number)>(id)
+        .filter(|(_, synced)| synced == value)
+        .map(|(text, _)| text)
+        .unwrap_or_else(|| value.to_string());
+
+    let mut res = None;
+    let valid = Number::from_str(text.trim()).ok();
+    let response = ui.add(
+        TextEdit::singleline(&mut text)
+            .desired_width(80.0)
+            .text_color_opt(valid.is_none().then_some(egui::Color32::RED)),
+    );
+    let mut synced = value.clone();
+    if response.changed() {
+        if let Ok(number) = Number::from_str(text.trim()) {
+            synced = number.clone();
+            res = Some(number);
+        }
+    }
+
+    ui.data().insert_temp(id, (text, synced));
+    res
+}
+
+/// Text being edited in a json editor, kept in egui's memory so invalid json can be fixed in place
+#[derive(Clone)]
+struct JsonEditor {
+    text: String,
+    error: Option<String>,
+    /// Value the text was last synced with
+    value: Value,
+}
+
+impl JsonEditor {
+    fn new(value: &Value) -> Self {
+        Self {
+            text: serde_json::to_string_pretty(value).unwrap_or_default(),
+            error: None,
+            value: value.clone(),
+        }
+    }
+}
+
+/// Multiline json editor with inline parse errors, returns the new value once the text parses
+fn json_editor(ui: &mut Ui, id: egui::Id, value: &Value, array: bool) -> Option<Value> {
+    let mut editor = ui
+        .data()
+        .get_temp::<JsonEditor>(id)
+        .unwrap_or_else(|| JsonEditor::new(value));
+
+    // The value was changed from somewhere else
+    if editor.value != *value {
+        editor = JsonEditor::new(value);
+    }
+
+    let mut res = None;
+    ui.vertical(|ui| {
+        let response = ui.add(
+            TextEdit::multiline(&mut editor.text)
+                .code_editor()
+                .desired_rows(3),
+        );
+
+        if response.changed() {
+            match serde_json::from_str::<Value>(&editor.text) {
+                Ok(parsed) if array && !parsed.is_array() => {
+                    editor.error = Some("Expected an array".to_string());
+                }
+                Ok(parsed) => {
+                    editor.error = None;
+                    editor.value = parsed.clone();
+                    res = Some(parsed);
+                }
+                Err(err) => editor.error = Some(err.to_string()),
+            }
+        }
+
+        match &editor.error {
+            Some(err) => {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+            None => {
+                if ui.small_button("Pretty").clicked() {
+                    editor = JsonEditor::new(&editor.value);
+                }
+            }
+        }
+    });
+
+    ui.data().insert_temp(id, editor);
+    res
+}
+
 impl Default for ValueType {
     fn default() -> Self {
         Self::Bool(false)
     }
 }
 
+impl ValueType {
+    /// Reads a json value, which may be missing, as the given type.
+    /// Missing and null values become null, strict mode only accepts them on optional types.
+    /// Other values are converted with `coerce`, strict mode requires them to already be
+    /// of the type instead.
+    pub fn from_json(value: Option<&Value>, data: &DataType, strict: bool) -> anyhow::Result<Self> {
+        let value = match value {
+            Some(Value::Null) | None => {
+                if strict && !data.is_optional() {
+                    anyhow::bail!("Missing value for {}", data.name());
+                }
+                return Ok(ValueType::Null);
+            }
+            Some(value) => value,
+        };
+
+        if !strict {
+            let value = match value {
+                Value::Bool(b) => ValueType::Bool(*b),
+                Value::Number(n) => ValueType::Number(n.clone()),
+                Value::String(s) => ValueType::String(s.clone()),
+                Value::Array(a) => ValueType::Array(a.clone()),
+                value => ValueType::Json(value.clone()),
+            };
+            return value.coerce(data);
+        }
+
+        let mismatch = || anyhow!("Expected {} but got {}", data.name(), value);
+
+        Ok(match data.inner() {
+            DataType::Bool => ValueType::Bool(value.as_bool().ok_or_else(mismatch)?),
+            DataType::Number => match value {
+                Value::Number(n) => ValueType::Number(n.clone()),
+                _ => return Err(mismatch()),
+            },
+            DataType::String => ValueType::String(value.as_str().ok_or_else(mismatch)?.to_string()),
+            DataType::Array(element) => {
+                let a = value.as_array().ok_or_else(mismatch)?;
+                for (i, item) in a.iter().enumerate() {
+                    ValueType::from_json(Some(item), element, strict)
+                        .map_err(|err| anyhow!("Element {}: {}", i, err))?;
+                }
+                ValueType::Array(a.clone())
+            }
+            DataType::Json => ValueType::Json(value.clone()),
+            DataType::Binary => match value.as_str().map(base64::decode) {
+                Some(Ok(b)) => ValueType::Binary(b),
+                _ => return Err(mismatch()),
+            },
+            DataType::Coin => ValueType::Coin(Coin::from_json(value)?),
+            DataType::Optional(_) => unreachable!("inner() removes the optional wrapper"),
+        })
+    }
+}
+
+impl ValueType {
+    /// Whether the value already is of the given type and needs no coercion
+    pub fn fits(&self, data: &DataType) -> bool {
+        match (self, data) {
+            (ValueType::Optional { value, set: true }, data) => value.fits(data),
+            (ValueType::Optional { set: false, .. }, data) => ValueType::Null.fits(data),
+            (ValueType::Null, DataType::Optional(_)) => true,
+            (value, DataType::Optional(data)) => value.fits(data),
+            (ValueType::Bool(_), DataType::Bool)
+            | (ValueType::Number(_), DataType::Number)
+            | (ValueType::String(_), DataType::String)
+            | (ValueType::Array(_), DataType::Array(_))
+            | (ValueType::Json(_), DataType::Json)
+            | (ValueType::Binary(_), DataType::Binary)
+            | (ValueType::Coin(_), DataType::Coin) => true,
+            _ => false,
+        }
+    }
+
+    /// The one place values are converted between types, every cast goes through here.
+    ///
+    /// | from \ to | bool     | number   | string      | array       | json     | binary | coin        |
+    /// |-----------|----------|----------|-------------|-------------|----------|--------|-------------|
+    /// | null      |          |          | `"null"`    |             | null     |        |             |
+    /// | bool      | =        | 0 or 1   | text        |             | bool     |        |             |
+    /// | number    | above 0  | =        | text        |             | number   |        |             |
+    /// | string    | parsed   | parsed   | =           |             | string   | base64 | `1000uscrt` |
+    /// | array     |          |          | json text   | =           | array    |        |             |
+    /// | json      | if bool  | if number| json text   | wrapped [1] | =        | base64 | if coin     |
+    /// | binary    |          |          | base64      |             | base64   | =      |             |
+    /// | coin      |          |          | `1000uscrt` |             | object   |        | =           |
+    ///
+    /// [1] json arrays are used as is, anything else becomes a single element array.
+    ///
+    /// Optional types accept null and otherwise coerce to the wrapped type.
+    /// Array element types are not checked.
+    ///
+    /// Connections only join matching types, so values change type here when a node
+    /// reads an input as another type, a splitter reads json or through a Convert node.
+    pub fn coerce(self, to: &DataType) -> anyhow::Result<ValueType> {
+        let value = self.resolved();
+        if let (ValueType::Null, DataType::Optional(_)) = (&value, to) {
+            return Ok(ValueType::Null);
+        }
+        let to = to.inner();
+
+        Ok(match (value, to) {
+            (value, to) if value.fits(to) => value,
+
+            (ValueType::Null, DataType::String) => ValueType::String("null".to_string()),
+
+            (ValueType::Bool(b), DataType::Number) => ValueType::Number(Number::from(b as i8)),
+            (ValueType::Bool(b), DataType::String) => ValueType::String(b.to_string()),
+
+            (ValueType::Number(n), DataType::Bool) => {
+                ValueType::Bool(n.as_f64().unwrap_or_default() > 0.0)
+            }
+            (ValueType::Number(n), DataType::String) => ValueType::String(n.to_string()),
+
+            (ValueType::String(s), DataType::Bool) => ValueType::Bool(
+                s.trim()
+                    .parse()
+                    .map_err(|_| anyhow!("Could not parse {} to boolean", s))?,
+            ),
+            (ValueType::String(s), DataType::Number) => ValueType::Number(
+                Number::from_str(s.trim())
+                    .map_err(|_| anyhow!("Could not parse {} to number", s))?,
+            ),
+            (ValueType::String(s), DataType::Binary) => ValueType::Binary(
+                base64::decode(&s).map_err(|_| anyhow!("Could not decode {} from base64", s))?,
+            ),
+            (ValueType::String(s), DataType::Coin) => ValueType::Coin(Coin::parse(&s)?),
+
+            (ValueType::Array(a), DataType::String) => {
+                ValueType::String(Value::Array(a).to_string())
+            }
+
+            (ValueType::Json(Value::Array(a)), DataType::Array(_)) => ValueType::Array(a),
+            (ValueType::Json(j), DataType::Array(_)) => ValueType::Array(vec![j]),
+            (ValueType::Json(j), DataType::String) => ValueType::String(j.to_string()),
+            (ValueType::Json(j), to) => ValueType::from_json(Some(&j), to, true)?,
+
+            (ValueType::Binary(b), DataType::String) => ValueType::String(base64::encode(b)),
+
+            (ValueType::Coin(c), DataType::String) => {
+                ValueType::String(format!("{}{}", c.amount, c.denom))
+            }
+
+            (value, DataType::Json) => ValueType::Json(value.try_into()?),
+            (value, to) => anyhow::bail!("Cannot convert {:?} to {}", value, to.name()),
+        })
+    }
+}
+
 impl TryInto<bool> for ValueType {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<bool, Self::Error> {
-        match self {
+        match self.coerce(&DataType::Bool)? {
             ValueType::Bool(b) => Ok(b),
-            ValueType::Number(n) => Ok(n.as_f64().unwrap() > 0.0),
-            _ => anyhow::bail!("Invalid cast from {:?} to boolean", self),
+            value => anyhow::bail!("Invalid cast from {:?} to boolean", value),
         }
     }
 }
@@ -130,15 +593,9 @@ impl TryInto<Number> for ValueType {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<Number, Self::Error> {
-        match self {
-            ValueType::Bool(b) => Ok(Number::from(b as i8)),
-            ValueType::String(s) => {
-                let n = s
-                    .parse::<f64>()
-                    .map_err(|_| anyhow!("Could not parse {} to float", s));
-                Ok(Number::from_f64(n?).unwrap())
-            }
-            _ => anyhow::bail!("Invalid cast from {:?} to float", self),
+        match self.coerce(&DataType::Number)? {
+            ValueType::Number(n) => Ok(n),
+            value => anyhow::bail!("Invalid cast from {:?} to number", value),
         }
     }
 }
@@ -146,12 +603,9 @@ impl TryInto<String> for ValueType {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<String, Self::Error> {
-        match self {
-            ValueType::Bool(b) => Ok(b.to_string()),
-            ValueType::Number(n) => Ok(n.to_string()),
+        match self.coerce(&DataType::String)? {
             ValueType::String(s) => Ok(s),
-            ValueType::Array(a) => Ok(format!("{:?}", a)),
-            ValueType::Json(j) => Ok(format!("{}", j.to_string())),
+            value => anyhow::bail!("Invalid cast from {:?} to string", value),
         }
     }
 }
@@ -160,11 +614,15 @@ impl TryInto<Value> for ValueType {
 
     fn try_into(self) -> Result<Value, Self::Error> {
         match self {
+            ValueType::Null => Ok(Value::Null),
             ValueType::Bool(b) => Ok(json!(b)),
             ValueType::Number(n) => Ok(json!(n)),
             ValueType::String(s) => Ok(json!(s)),
             ValueType::Array(a) => Ok(json!(a)),
             ValueType::Json(j) => Ok(j),
+            ValueType::Binary(b) => Ok(json!(base64::encode(b))),
+            ValueType::Coin(c) => Ok(json!({ "amount": c.amount, "denom": c.denom })),
+            optional @ ValueType::Optional { .. } => optional.resolved().try_into(),
         }
     }
 }
@@ -173,10 +631,313 @@ impl TryInto<Vec<Value>> for ValueType {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<Vec<Value>, Self::Error> {
-        match self {
+        match self.coerce(&DataType::Array(Box::new(DataType::Json)))? {
             ValueType::Array(a) => Ok(a),
-            ValueType::Json(j) => Ok(vec![j]),
-            _ => anyhow::bail!("Not gonna bother casting {:?} to vec", self),
+            value => anyhow::bail!("Invalid cast from {:?} to array", value),
         }
     }
 }
+
+impl TryInto<Vec<u8>> for ValueType {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<Vec<u8>, Self::Error> {
+        match self.coerce(&DataType::Binary)? {
+            ValueType::Binary(b) => Ok(b),
+            value => anyhow::bail!("Invalid cast from {:?} to binary", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(n: &str) -> ValueType {
+        ValueType::Number(Number::from_str(n).unwrap())
+    }
+
+    fn string(s: &str) -> ValueType {
+        ValueType::String(s.to_string())
+    }
+
+    fn coin() -> Coin {
+        Coin::new("1000".to_string(), "uscrt".to_string()).unwrap()
+    }
+
+    fn array() -> DataType {
+        DataType::Array(Box::new(DataType::Number))
+    }
+
+    fn optional(data: DataType) -> DataType {
+        DataType::Optional(Box::new(data))
+    }
+
+    #[test]
+    fn null_row() {
+        assert_eq!(
+            ValueType::Null.coerce(&DataType::String).unwrap(),
+            string("null")
+        );
+        assert_eq!(
+            ValueType::Null.coerce(&DataType::Json).unwrap(),
+            ValueType::Json(Value::Null)
+        );
+        assert!(ValueType::Null.coerce(&DataType::Number).is_err());
+        assert!(ValueType::Null.coerce(&DataType::Bool).is_err());
+    }
+
+    #[test]
+    fn bool_row() {
+        let t = ValueType::Bool(true);
+        assert_eq!(t.clone().coerce(&DataType::Bool).unwrap(), t);
+        assert_eq!(t.clone().coerce(&DataType::Number).unwrap(), number("1"));
+        assert_eq!(
+            ValueType::Bool(false).coerce(&DataType::Number).unwrap(),
+            number("0")
+        );
+        assert_eq!(t.clone().coerce(&DataType::String).unwrap(), string("true"));
+        assert_eq!(
+            t.clone().coerce(&DataType::Json).unwrap(),
+            ValueType::Json(json!(true))
+        );
+        assert!(t.clone().coerce(&array()).is_err());
+        assert!(t.clone().coerce(&DataType::Binary).is_err());
+        assert!(t.coerce(&DataType::Coin).is_err());
+    }
+
+    #[test]
+    fn number_row() {
+        assert_eq!(
+            number("0.5").coerce(&DataType::Bool).unwrap(),
+            ValueType::Bool(true)
+        );
+        assert_eq!(
+            number("0").coerce(&DataType::Bool).unwrap(),
+            ValueType::Bool(false)
+        );
+        assert_eq!(
+            number("-3").coerce(&DataType::Bool).unwrap(),
+            ValueType::Bool(false)
+        );
+        assert_eq!(number("7").coerce(&DataType::Number).unwrap(), number("7"));
+        // Precision survives the trip through text
+        let big = "340282366920938463463374607431768211455";
+        assert_eq!(number(big).coerce(&DataType::String).unwrap(), string(big));
+        assert_eq!(
+            number("1.5").coerce(&DataType::Json).unwrap(),
+            ValueType::Json(json!(1.5))
+        );
+        assert!(number("1").coerce(&array()).is_err());
+        assert!(number("1").coerce(&DataType::Binary).is_err());
+        assert!(number("1").coerce(&DataType::Coin).is_err());
+    }
+
+    #[test]
+    fn string_row() {
+        assert_eq!(
+            string(" true ").coerce(&DataType::Bool).unwrap(),
+            ValueType::Bool(true)
+        );
+        assert!(string("yes").coerce(&DataType::Bool).is_err());
+        assert_eq!(
+            string(" 42 ").coerce(&DataType::Number).unwrap(),
+            number("42")
+        );
+        assert!(string("4 2").coerce(&DataType::Number).is_err());
+        assert_eq!(string("a").coerce(&DataType::String).unwrap(), string("a"));
+        assert!(string("[1]").coerce(&array()).is_err());
+        assert_eq!(
+            string("a").coerce(&DataType::Json).unwrap(),
+            ValueType::Json(json!("a"))
+        );
+        assert_eq!(
+            string("aGk=").coerce(&DataType::Binary).unwrap(),
+            ValueType::Binary(b"hi".to_vec())
+        );
+        assert!(string("not base64!").coerce(&DataType::Binary).is_err());
+        assert_eq!(
+            string("1000uscrt").coerce(&DataType::Coin).unwrap(),
+            ValueType::Coin(coin())
+        );
+        assert!(string("uscrt").coerce(&DataType::Coin).is_err());
+        assert!(string("1000").coerce(&DataType::Coin).is_err());
+    }
+
+    #[test]
+    fn array_row() {
+        let a = ValueType::Array(vec![json!(1), json!("a")]);
+        assert!(a.clone().coerce(&DataType::Bool).is_err());
+        assert!(a.clone().coerce(&DataType::Number).is_err());
+        assert_eq!(
+            a.clone().coerce(&DataType::String).unwrap(),
+            string(r#"[1,"a"]"#)
+        );
+        // Element types are not checked
+        assert_eq!(a.clone().coerce(&array()).unwrap(), a);
+        assert_eq!(
+            a.clone().coerce(&DataType::Json).unwrap(),
+            ValueType::Json(json!([1, "a"]))
+        );
+        assert!(a.clone().coerce(&DataType::Binary).is_err());
+        assert!(a.coerce(&DataType::Coin).is_err());
+    }
+
+    #[test]
+    fn json_row() {
+        let j = |value: Value| ValueType::Json(value);
+        assert_eq!(
+            j(json!(true)).coerce(&DataType::Bool).unwrap(),
+            ValueType::Bool(true)
+        );
+        assert!(j(json!("true")).coerce(&DataType::Bool).is_err());
+        assert_eq!(j(json!(3)).coerce(&DataType::Number).unwrap(), number("3"));
+        assert!(j(json!("3")).coerce(&DataType::Number).is_err());
+        assert_eq!(
+            j(json!({"a": 1})).coerce(&DataType::String).unwrap(),
+            string(r#"{"a":1}"#)
+        );
+        assert_eq!(
+            j(json!([1, 2])).coerce(&array()).unwrap(),
+            ValueType::Array(vec![json!(1), json!(2)])
+        );
+        assert_eq!(
+            j(json!({"a": 1})).coerce(&array()).unwrap(),
+            ValueType::Array(vec![json!({"a": 1})])
+        );
+        assert_eq!(
+            j(json!({"a": 1})).coerce(&DataType::Json).unwrap(),
+            j(json!({"a": 1}))
+        );
+        assert_eq!(
+            j(json!("aGk=")).coerce(&DataType::Binary).unwrap(),
+            ValueType::Binary(b"hi".to_vec())
+        );
+        assert!(j(json!(1)).coerce(&DataType::Binary).is_err());
+        assert_eq!(
+            j(json!({"amount": "1000", "denom": "uscrt"}))
+                .coerce(&DataType::Coin)
+                .unwrap(),
+            ValueType::Coin(coin())
+        );
+        assert!(j(json!({"amount": "-1", "denom": "uscrt"}))
+            .coerce(&DataType::Coin)
+            .is_err());
+    }
+
+    #[test]
+    fn binary_row() {
+        let b = ValueType::Binary(b"hi".to_vec());
+        assert!(b.clone().coerce(&DataType::Bool).is_err());
+        assert!(b.clone().coerce(&DataType::Number).is_err());
+        assert_eq!(b.clone().coerce(&DataType::String).unwrap(), string("aGk="));
+        assert!(b.clone().coerce(&array()).is_err());
+        assert_eq!(
+            b.clone().coerce(&DataType::Json).unwrap(),
+            ValueType::Json(json!("aGk="))
+        );
+        assert_eq!(b.clone().coerce(&DataType::Binary).unwrap(), b);
+        assert!(b.coerce(&DataType::Coin).is_err());
+    }
+
+    #[test]
+    fn coin_row() {
+        let c = ValueType::Coin(coin());
+        assert!(c.clone().coerce(&DataType::Bool).is_err());
+        assert!(c.clone().coerce(&DataType::Number).is_err());
+        assert_eq!(
+            c.clone().coerce(&DataType::String).unwrap(),
+            string("1000uscrt")
+        );
+        assert!(c.clone().coerce(&array()).is_err());
+        assert_eq!(
+            c.clone().coerce(&DataType::Json).unwrap(),
+            ValueType::Json(json!({"amount": "1000", "denom": "uscrt"}))
+        );
+        assert!(c.clone().coerce(&DataType::Binary).is_err());
+        assert_eq!(c.clone().coerce(&DataType::Coin).unwrap(), c);
+    }
+
+    #[test]
+    fn optionals_accept_null_and_coerce_to_the_wrapped_type() {
+        let number_or_null = optional(DataType::Number);
+        assert_eq!(
+            ValueType::Null.coerce(&number_or_null).unwrap(),
+            ValueType::Null
+        );
+        assert_eq!(string("5").coerce(&number_or_null).unwrap(), number("5"));
+
+        let unset = ValueType::Optional {
+            value: Box::new(number("5")),
+            set: false,
+        };
+        assert_eq!(unset.coerce(&number_or_null).unwrap(), ValueType::Null);
+        let set = ValueType::Optional {
+            value: Box::new(string("5")),
+            set: true,
+        };
+        assert_eq!(set.coerce(&number_or_null).unwrap(), number("5"));
+    }
+
+    #[test]
+    fn strict_mode_requires_exact_types() {
+        let read = |value: Value, data: &DataType| ValueType::from_json(Some(&value), data, true);
+
+        assert!(read(json!("5"), &DataType::Number).is_err());
+        assert!(read(json!(1), &DataType::Bool).is_err());
+        assert!(read(json!(5), &DataType::String).is_err());
+        assert!(read(json!({}), &array()).is_err());
+        assert!(read(json!("not base64!"), &DataType::Binary).is_err());
+        assert!(read(json!("1000uscrt"), &DataType::Coin).is_err());
+        let err = read(json!([1, "2"]), &array()).unwrap_err();
+        assert!(err.to_string().starts_with("Element 1"));
+
+        assert_eq!(read(json!(5), &DataType::Number).unwrap(), number("5"));
+        assert_eq!(
+            read(json!(5), &optional(DataType::Number)).unwrap(),
+            number("5")
+        );
+        assert_eq!(
+            read(json!([1, 2]), &array()).unwrap(),
+            ValueType::Array(vec![json!(1), json!(2)])
+        );
+    }
+
+    #[test]
+    fn strict_mode_only_allows_null_on_optionals() {
+        for value in [None, Some(&Value::Null)] {
+            assert!(ValueType::from_json(value, &DataType::Number, true).is_err());
+            assert_eq!(
+                ValueType::from_json(value, &optional(DataType::Number), true).unwrap(),
+                ValueType::Null
+            );
+            assert_eq!(
+                ValueType::from_json(value, &DataType::Number, false).unwrap(),
+                ValueType::Null
+            );
+        }
+        // Lenient mode coerces instead
+        assert_eq!(
+            ValueType::from_json(Some(&json!("5")), &DataType::Number, false).unwrap(),
+            number("5")
+        );
+    }
+
+    #[test]
+    fn equality_ignores_the_optional_wrapper() {
+        assert_eq!(optional(DataType::Number), DataType::Number);
+        assert_eq!(DataType::Number, optional(DataType::Number));
+        assert_eq!(optional(array()), array());
+        // Only the outer wrapper, element types compare exactly
+        assert_ne!(
+            DataType::Array(Box::new(optional(DataType::Number))),
+            array()
+        );
+        assert_ne!(optional(DataType::Number), DataType::String);
+        assert_ne!(array(), DataType::Array(Box::new(DataType::String)));
+
+        // Exact comparison still tells them apart
+        assert!(!optional(DataType::Number).is(&DataType::Number));
+        assert!(optional(DataType::Number).is(&optional(DataType::Number)));
+    }
+}
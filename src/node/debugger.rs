@@ -0,0 +1,602 @@
+use crate::node::chain::MockChain;
+use crate::node::data::ValueType;
+use crate::node::evaluator::{
+    evaluate_input, evaluate_node, evaluation_order, for_each_elements, peek_inputs,
+    populate_for_each, Environment, OutputsCache,
+};
+use crate::node::manifest::Manifest;
+use crate::node::template::Template;
+use crate::node::transcript::{Status, Transcript, TranscriptEntry, Tx};
+use crate::node::wasm::WasmCache;
+use crate::node::NodeGraph;
+use anyhow::anyhow;
+use egui_node_graph::NodeId;
+use serde_json::{Number, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Evaluates a node one dependency at a time, pausing before each one
+pub struct Debugger {
+    order: Vec<Step>,
+    position: usize,
+    /// For Each nodes whose body is being stepped through
+    loops: HashMap<NodeId, Loop>,
+    outputs_cache: OutputsCache,
+    deployment: Option<Manifest>,
+    dry_run: bool,
+    wasm: WasmCache,
+    chain: MockChain,
+    /// Inputs of the paused node as they would be received
+    pub inputs: Vec<(String, Option<ValueType>)>,
+    /// Last evaluation error, the debugger stays on the failing node
+    pub error: Option<String>,
+    /// Final result of the debugged node
+    pub result: Option<Vec<String>>,
+    /// Everything that happened so far
+    pub transcript: Transcript,
+}
+
+/// Only node steps pause, the loop steps run on their own
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// Evaluates the node, its lazy inputs are entered first
+    Node(NodeId),
+    /// Evaluates the node once its lazy inputs were entered
+    Entered(NodeId),
+    /// Sets the For Each element the body is evaluated with
+    Element(NodeId, usize),
+    /// Collects the body result of the element
+    Result(NodeId, usize),
+}
+
+struct Loop {
+    elements: Vec<ValueType>,
+    results: Vec<Value>,
+    /// First element the body failed on
+    failed: Option<String>,
+    /// Outputs from before the element, restored after it so the body is evaluated again
+    outer: OutputsCache,
+}
+
+impl Loop {
+    /// Fills the For Each outputs with the results collected from its body
+    fn finish(
+        self,
+        graph: &NodeGraph,
+        outputs_cache: &mut OutputsCache,
+        node_id: NodeId,
+    ) -> anyhow::Result<Vec<String>> {
+        if let Some(err) = self.failed {
+            anyhow::bail!(err);
+        }
+        match &graph[node_id].user_data.template {
+            Template::ForEach(from, _) => {
+                populate_for_each(graph, outputs_cache, node_id, from, self.results)
+            }
+            _ => anyhow::bail!("Only For Each nodes loop"),
+        }
+    }
+}
+
+impl Debugger {
+    /// Starts evaluating and only pauses on breakpoints
+    pub fn run(
+        graph: &NodeGraph,
+        node_id: NodeId,
+        deployment: Option<Manifest>,
+        dry_run: bool,
+    ) -> Self {
+        let mut debugger = Self::new(graph, node_id, deployment, dry_run);
+        if !debugger.at_breakpoint(graph) {
+            debugger.resume(graph);
+        }
+        debugger
+    }
+
+    /// Evaluates everything ignoring breakpoints, stops at the first error
+    pub fn run_to_end(
+        graph: &NodeGraph,
+        node_id: NodeId,
+        deployment: Option<Manifest>,
+        dry_run: bool,
+    ) -> Self {
+        let mut debugger = Self::new(graph, node_id, deployment, dry_run);
+        while !debugger.is_finished() && debugger.error.is_none() {
+            debugger.step(graph);
+        }
+        debugger
+    }
+
+    pub fn new(
+        graph: &NodeGraph,
+        node_id: NodeId,
+        deployment: Option<Manifest>,
+        dry_run: bool,
+    ) -> Self {
+        let mut debugger = Self {
+            order: evaluation_order(graph, node_id)
+                .into_iter()
+                .map(Step::Node)
+                .collect(),
+            position: 0,
+            loops: HashMap::new(),
+            outputs_cache: OutputsCache::new(),
+            deployment,
+            dry_run,
+            wasm: WasmCache::default(),
+            chain: MockChain,
+            inputs: vec![],
+            error: None,
+            result: None,
+            transcript: Transcript {
+                dry_run,
+                ..Default::default()
+            },
+        };
+        debugger.refresh(graph);
+        debugger
+    }
+
+    /// The node we're paused at
+    pub fn current(&self) -> Option<NodeId> {
+        match self.order.get(self.position) {
+            Some(Step::Node(node_id) | Step::Entered(node_id)) => Some(*node_id),
+            _ => None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.order.len()
+    }
+
+    /// Returns the current step and the total amount of steps, entering branches and loops adds steps
+    pub fn progress(&self) -> (usize, usize) {
+        let nodes = |steps: &[Step]| {
+            steps
+                .iter()
+                .filter(|step| matches!(step, Step::Node(_) | Step::Entered(_)))
+                .count()
+        };
+        (nodes(&self.order[..self.position]), nodes(&self.order))
+    }
+
+    /// Evaluates the paused node and moves onto the next one
+    pub fn step(&mut self, graph: &NodeGraph) {
+        let node_id = match self.current() {
+            Some(node_id) if graph.nodes.contains_key(node_id) => node_id,
+            _ => return self.finish(graph),
+        };
+
+        let start = Instant::now();
+        let evaluated = match self.loops.remove(&node_id) {
+            // The body was already stepped through for every element
+            Some(current) => current.finish(graph, &mut self.outputs_cache, node_id),
+            None => evaluate_node(
+                graph,
+                node_id,
+                &mut self.outputs_cache,
+                Environment {
+                    deployment: self.deployment.as_ref(),
+                    dry_run: self.dry_run,
+                    wasm: &self.wasm,
+                    chain: &self.chain,
+                },
+            ),
+        };
+        let status = match evaluated {
+            Ok(result) => {
+                self.error = None;
+                self.position += 1;
+                if self.is_finished() {
+                    self.result = Some(result);
+                }
+                Status::Evaluated
+            }
+            Err(err) => {
+                self.error = Some(err.to_string());
+                Status::Failed(err.to_string())
+            }
+        };
+
+        self.record(graph, node_id, start.elapsed(), status);
+        self.refresh(graph);
+    }
+
+    /// Fills the paused node outputs with empty values instead of evaluating it
+    pub fn skip(&mut self, graph: &NodeGraph) {
+        let node_id = match self.current() {
+            Some(node_id) if graph.nodes.contains_key(node_id) => node_id,
+            _ => return self.finish(graph),
+        };
+
+        for (_, output_id) in graph[node_id].outputs.iter() {
+            self.outputs_cache
+                .insert(*output_id, graph[*output_id].typ.default_value());
+        }
+
+        self.loops.remove(&node_id);
+        self.error = None;
+        self.position += 1;
+        self.record(graph, node_id, Duration::ZERO, Status::Skipped);
+        self.refresh(graph);
+    }
+
+    /// Keeps stepping until everything is evaluated, something fails or a breakpoint is hit
+    pub fn resume(&mut self, graph: &NodeGraph) {
+        while !self.is_finished() {
+            self.step(graph);
+            if self.error.is_some() || self.at_breakpoint(graph) {
+                break;
+            }
+        }
+    }
+
+    /// Checks if the paused node is a breakpoint and its condition holds
+    fn at_breakpoint(&mut self, graph: &NodeGraph) -> bool {
+        let node = match self.current() {
+            Some(node_id) if graph.nodes.contains_key(node_id) => &graph[node_id].user_data,
+            _ => return false,
+        };
+
+        if !node.breakpoint {
+            return false;
+        }
+
+        match condition_met(&node.condition, &self.inputs) {
+            Ok(met) => met,
+            Err(err) => {
+                // Better to stop and say why than to run past it
+                self.error = Some(format!("Breakpoint condition error: {}", err));
+                true
+            }
+        }
+    }
+
+    /// Adds the node to the transcript, the inputs are still the ones from before evaluating
+    fn record(&mut self, graph: &NodeGraph, node_id: NodeId, duration: Duration, status: Status) {
+        let outputs: Vec<_> = graph[node_id]
+            .outputs
+            .iter()
+            .filter_map(|(name, output_id)| {
+                self.outputs_cache
+                    .get(output_id)
+                    .map(|value| (name.clone(), value.clone()))
+            })
+            .collect();
+
+        self.transcript.entries.push(TranscriptEntry {
+            node: graph[node_id].label.clone(),
+            inputs: self.inputs.clone(),
+            tx: Tx::from_outputs(&outputs),
+            outputs,
+            duration,
+            status,
+        });
+    }
+
+    fn finish(&mut self, graph: &NodeGraph) {
+        self.position = self.order.len();
+        self.refresh(graph);
+    }
+
+    /// Enters the lazy inputs of the paused node once the values deciding them are known,
+    /// the branch a select takes or a for each's body once per element.
+    /// Returns false if there was nothing to enter.
+    fn expand(&mut self, graph: &NodeGraph) -> bool {
+        let node_id = match self.order.get(self.position) {
+            Some(Step::Node(node_id)) if graph.nodes.contains_key(*node_id) => *node_id,
+            _ => return false,
+        };
+        self.order[self.position] = Step::Entered(node_id);
+
+        let inputs = peek_inputs(graph, node_id, &self.outputs_cache);
+        let input = |name: &str| {
+            inputs
+                .iter()
+                .find_map(|(input, value)| if input == name { value.clone() } else { None })
+        };
+
+        // Anything unexpected fails when evaluating the node
+        let (steps, replaces) = match &graph[node_id].user_data.template {
+            Template::Select(_) => {
+                let branch = match input("cond") {
+                    Some(ValueType::Bool(true)) => "then",
+                    Some(ValueType::Bool(false)) => "else",
+                    _ => return false,
+                };
+                let steps: Vec<_> = self
+                    .pending(graph, node_id, branch)
+                    .into_iter()
+                    .map(Step::Node)
+                    .collect();
+                (steps, true)
+            }
+            Template::ForEach(from, _) => {
+                let strict = graph[node_id].user_data.strict;
+                let elements = match input("array")
+                    .and_then(|array| array.try_into().ok())
+                    .and_then(|array: Vec<Value>| for_each_elements(&array, from, strict).ok())
+                {
+                    Some(elements) => elements,
+                    None => return false,
+                };
+
+                let body = self.pending(graph, node_id, "result");
+                let mut steps = vec![];
+                for i in 0..elements.len() {
+                    steps.push(Step::Element(node_id, i));
+                    steps.extend(body.iter().copied().map(Step::Node));
+                    steps.push(Step::Result(node_id, i));
+                }
+                self.loops.insert(
+                    node_id,
+                    Loop {
+                        elements,
+                        results: vec![],
+                        failed: None,
+                        outer: OutputsCache::new(),
+                    },
+                );
+                // The body is evaluated again whenever it's needed after the loop
+                (steps, false)
+            }
+            _ => return false,
+        };
+        if steps.is_empty() {
+            return false;
+        }
+
+        let mut rest = self.order.split_off(self.position);
+        if replaces {
+            // Nodes also needed later on are evaluated now instead,
+            // up to the end of the loop element we might be in
+            let end = rest
+                .iter()
+                .position(|step| matches!(step, Step::Element(..) | Step::Result(..)))
+                .unwrap_or(rest.len());
+            let after = rest.split_off(end);
+            rest.retain(|step| !steps.contains(step));
+            rest.extend(after);
+        }
+        self.order.extend(steps);
+        self.order.extend(rest);
+        true
+    }
+
+    /// Nodes a lazy input needs that haven't been evaluated yet, dependencies first
+    fn pending(&self, graph: &NodeGraph, node_id: NodeId, name: &str) -> Vec<NodeId> {
+        let source = match graph[node_id]
+            .get_input(name)
+            .ok()
+            .and_then(|input| graph.connection(input))
+        {
+            Some(output) => graph[output].node,
+            None => return vec![],
+        };
+
+        evaluation_order(graph, source)
+            .into_iter()
+            .filter(|id| {
+                *id != node_id
+                    && !graph[*id]
+                        .outputs
+                        .iter()
+                        .any(|(_, output)| self.outputs_cache.contains_key(output))
+            })
+            .collect()
+    }
+
+    /// Sets the element before the loop body or collects its result after it
+    fn advance_loop(&mut self, graph: &NodeGraph) {
+        let step = self.order[self.position];
+        self.position += 1;
+
+        let (node_id, i) = match step {
+            Step::Element(node_id, i) | Step::Result(node_id, i) => (node_id, i),
+            _ => return,
+        };
+        let current = match self.loops.get_mut(&node_id) {
+            Some(current) if graph.nodes.contains_key(node_id) => current,
+            _ => return,
+        };
+
+        if let Step::Element(..) = step {
+            current.outer = self.outputs_cache.clone();
+            let values = [
+                ("element", current.elements[i].clone()),
+                ("index", ValueType::Number(Number::from(i))),
+            ];
+            for (name, value) in values {
+                if let Ok(output) = graph[node_id].get_output(name) {
+                    self.outputs_cache.insert(output, value);
+                }
+            }
+            return;
+        }
+
+        let result = evaluate_input(
+            graph,
+            node_id,
+            "result",
+            &mut self.outputs_cache,
+            Environment {
+                deployment: self.deployment.as_ref(),
+                dry_run: self.dry_run,
+                wasm: &self.wasm,
+                chain: &self.chain,
+            },
+        )
+        .and_then(|result| result.try_into());
+        match result {
+            Ok(result) => current.results.push(result),
+            Err(err) => {
+                current
+                    .failed
+                    .get_or_insert_with(|| format!("{}: {}", i, err));
+            }
+        }
+        self.outputs_cache = std::mem::take(&mut current.outer);
+    }
+
+    fn refresh(&mut self, graph: &NodeGraph) {
+        loop {
+            match self.order.get(self.position) {
+                Some(Step::Element(..) | Step::Result(..)) => self.advance_loop(graph),
+                // What's entered can start with another select or loop
+                Some(Step::Node(_)) if self.expand(graph) => {}
+                _ => break,
+            }
+        }
+
+        self.inputs = match self.current() {
+            Some(node_id) if graph.nodes.contains_key(node_id) => {
+                peek_inputs(graph, node_id, &self.outputs_cache)
+            }
+            _ => vec![],
+        };
+    }
+}
+
+/// Evaluates a breakpoint condition against the node inputs.
+///
+/// Conditions look like `input`, `input == value` or `input/json/pointer > value`,
+/// supported operators are `==`, `!=`, `<`, `<=`, `>` and `>=`.
+/// Values are read as json, falling back to plain strings. An empty condition always pauses.
+pub fn condition_met(
+    condition: &str,
+    inputs: &[(String, Option<ValueType>)],
+) -> anyhow::Result<bool> {
+    let condition = condition.trim();
+    if condition.is_empty() {
+        return Ok(true);
+    }
+
+    // The leftmost operator splits the condition so values can contain operators,
+    // longer operators go first so `<=` isn't read as `<`
+    let operator = ["==", "!=", "<=", ">=", "<", ">"]
+        .into_iter()
+        .filter_map(|op| condition.find(op).map(|i| (op, i)))
+        .min_by_key(|(op, i)| (*i, usize::MAX - op.len()));
+
+    let (lhs, rhs) = match operator {
+        Some((op, i)) => (&condition[..i], Some((op, &condition[i + op.len()..]))),
+        None => (condition, None),
+    };
+
+    let lhs = lhs.trim();
+    let (name, pointer) = match lhs.find('/') {
+        Some(i) => (&lhs[..i], &lhs[i..]),
+        None => (lhs, ""),
+    };
+
+    let value = inputs
+        .iter()
+        .find(|(input, _)| input == name)
+        .ok_or_else(|| anyhow!("No input named {}", name))?
+        .1
+        .clone()
+        .ok_or_else(|| anyhow!("Input {} has not been evaluated", name))?;
+    let value: Value = value.try_into()?;
+    let value = value
+        .pointer(pointer)
+        .ok_or_else(|| anyhow!("Could not find {} in {}", pointer, name))?;
+
+    let (op, expected) = match rhs {
+        Some((op, rhs)) => {
+            let rhs = rhs.trim();
+            let expected =
+                serde_json::from_str(rhs).unwrap_or_else(|_| Value::String(rhs.to_string()));
+            (op, expected)
+        }
+        None => {
+            return Ok(match value {
+                Value::Null => false,
+                Value::Bool(b) => *b,
+                Value::Number(n) => n.as_f64().unwrap_or_default() != 0.0,
+                Value::String(s) => !s.is_empty(),
+                Value::Array(a) => !a.is_empty(),
+                Value::Object(o) => !o.is_empty(),
+            })
+        }
+    };
+
+    match op {
+        "==" => Ok(*value == expected),
+        "!=" => Ok(*value != expected),
+        _ => {
+            let as_number = |value: &Value| match value {
+                Value::Number(n) => n.as_f64(),
+                Value::String(s) => s.parse::<f64>().ok(),
+                _ => None,
+            };
+            let (lhs, rhs) = as_number(value)
+                .zip(as_number(&expected))
+                .ok_or_else(|| anyhow!("Can only compare numbers with {}", op))?;
+
+            Ok(match op {
+                "<" => lhs < rhs,
+                "<=" => lhs <= rhs,
+                ">" => lhs > rhs,
+                _ => lhs >= rhs,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Number};
+
+    fn inputs() -> Vec<(String, Option<ValueType>)> {
+        vec![
+            (
+                "amount".to_string(),
+                Some(ValueType::Number(Number::from(5))),
+            ),
+            (
+                "label".to_string(),
+                Some(ValueType::String("a<b".to_string())),
+            ),
+            (
+                "msg".to_string(),
+                Some(ValueType::Json(json!({"transfer": {"amount": "10"}}))),
+            ),
+            ("pending".to_string(), None),
+        ]
+    }
+
+    #[test]
+    fn empty_condition_always_pauses() {
+        assert!(condition_met("  ", &inputs()).unwrap());
+    }
+
+    #[test]
+    fn longer_operators_take_precedence() {
+        assert!(condition_met("amount <= 5", &inputs()).unwrap());
+        assert!(condition_met("amount >= 5", &inputs()).unwrap());
+        assert!(!condition_met("amount < 5", &inputs()).unwrap());
+        assert!(condition_met("amount != 4", &inputs()).unwrap());
+    }
+
+    #[test]
+    fn leftmost_operator_splits() {
+        assert!(condition_met("label == a<b", &inputs()).unwrap());
+        assert!(!condition_met("label != a<b", &inputs()).unwrap());
+    }
+
+    #[test]
+    fn pointers_and_string_numbers() {
+        assert!(condition_met("msg/transfer/amount > 9", &inputs()).unwrap());
+        assert!(condition_met("msg/transfer/amount == \"10\"", &inputs()).unwrap());
+        assert!(condition_met("msg/transfer", &inputs()).unwrap());
+        assert!(condition_met("msg/missing", &inputs()).is_err());
+    }
+
+    #[test]
+    fn missing_or_unevaluated_inputs_error() {
+        assert!(condition_met("nope", &inputs()).is_err());
+        assert!(condition_met("pending == 1", &inputs()).is_err());
+        assert!(condition_met("label < 3", &inputs()).is_err());
+    }
+}
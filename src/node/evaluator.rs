@@ -1,18 +1,41 @@
-use crate::node::data::{DataType, ValueType};
+use crate::node::chain::ChainClient;
+use crate::node::data::{Coin, DataType, ValueType};
+use crate::node::fee::Fees;
+use crate::node::format::{interpolate, TEMPLATE_INPUT};
+use crate::node::manifest::Manifest;
+use crate::node::patch::{apply, merge};
+use crate::node::path::{select, set};
+use crate::node::schema::{MsgKind, Variant};
+use crate::node::subgraph::Port;
 use crate::node::template::Template;
-use crate::node::NodeGraph;
+use crate::node::wasm::{WasmCache, DEFAULT_MAX_WASM_SIZE};
+use crate::node::{GraphState, NodeGraph};
+use anyhow::anyhow;
 use egui_node_graph::{NodeId, OutputId};
-use serde_json::{Map, Number, Value};
-use std::collections::HashMap;
+use serde_json::{json, Map, Number, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::str::FromStr;
 
-type OutputsCache = HashMap<OutputId, ValueType>;
+pub type OutputsCache = HashMap<OutputId, ValueType>;
+
+/// What the effectful nodes are evaluated against
+#[derive(Clone, Copy)]
+pub struct Environment<'a> {
+    /// Previous deployment whose outputs can be reused
+    pub deployment: Option<&'a Manifest>,
+    /// Effectful nodes simulate their txs instead of broadcasting them
+    pub dry_run: bool,
+    pub wasm: &'a WasmCache,
+    pub chain: &'a dyn ChainClient,
+}
 
 // Recursively evaluates all dependencies of this node, then evaluates the node itself.
 pub fn evaluate_node(
     graph: &NodeGraph,
     node_id: NodeId,
     outputs_cache: &mut OutputsCache,
+    env: Environment,
 ) -> anyhow::Result<Vec<String>> {
     // To solve a similar problem as creating node types above, we define an
     // Evaluator as a convenience. It may be overkill for this small example,
@@ -23,19 +46,26 @@ pub fn evaluate_node(
         graph: &'a NodeGraph,
         outputs_cache: &'a mut OutputsCache,
         node_id: NodeId,
+        env: Environment<'a>,
     }
     impl<'a> Evaluator<'a> {
-        fn new(graph: &'a NodeGraph, outputs_cache: &'a mut OutputsCache, node_id: NodeId) -> Self {
+        fn new(
+            graph: &'a NodeGraph,
+            outputs_cache: &'a mut OutputsCache,
+            node_id: NodeId,
+            env: Environment<'a>,
+        ) -> Self {
             Self {
                 graph,
                 outputs_cache,
                 node_id,
+                env,
             }
         }
         fn evaluate_input(&mut self, name: &str) -> anyhow::Result<ValueType> {
             // Calling `evaluate_input` recursively evaluates other nodes in the
             // graph until the input value for a parameter has been computed.
-            evaluate_input(self.graph, self.node_id, name, self.outputs_cache)
+            evaluate_input(self.graph, self.node_id, name, self.outputs_cache, self.env)
         }
         fn populate_output(&mut self, name: &str, value: ValueType) -> anyhow::Result<ValueType> {
             // TODO: improve with cache comparation
@@ -54,11 +84,100 @@ pub fn evaluate_node(
             // the graphs, you can come up with your own evaluation semantics!
             populate_output(self.graph, self.outputs_cache, self.node_id, name, value)
         }
+        /// Builds a msg from the inputs generated for the variant's fields
+        fn evaluate_msg(&mut self, variant: &Variant, prefix: &str) -> anyhow::Result<Value> {
+            let mut fields = Map::new();
+            for (name, _) in variant.fields.iter() {
+                let value: Value = self
+                    .evaluate_input(&format!("{}{}", prefix, name))?
+                    .try_into()?;
+                // Skip unset optional fields
+                if !value.is_null() {
+                    fields.insert(name.clone(), value);
+                }
+            }
+            Ok(variant.msg(fields))
+        }
+        /// Coins sent along with the msg, sorted by denom like the chain expects
+        fn evaluate_funds(&mut self) -> anyhow::Result<Vec<Coin>> {
+            let funds: Vec<Value> = self.evaluate_input("funds")?.try_into()?;
+            let mut coins = funds
+                .iter()
+                .enumerate()
+                .map(|(i, coin)| {
+                    Coin::from_json(coin).map_err(|err| anyhow!("Funds {}: {}", i, err))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+            if let Some(pair) = coins.windows(2).find(|pair| pair[0].denom == pair[1].denom) {
+                anyhow::bail!("Funds can only have one {} coin", pair[0].denom);
+            }
+            Ok(coins)
+        }
+        fn evaluate_fees(&mut self) -> anyhow::Result<Fees> {
+            let gas: String = self.evaluate_input("gas")?.try_into()?;
+            let mut decimal = |name: &str| -> anyhow::Result<f64> {
+                let number: Number = self.evaluate_input(name)?.try_into()?;
+                number
+                    .as_f64()
+                    .ok_or_else(|| anyhow!("{} must be a decimal, not {}", name, number))
+            };
+            let multiplier = decimal("gas_multiplier")?;
+            let price = decimal("gas_price")?;
+            let denom: String = self.evaluate_input("fee_denom")?.try_into()?;
+            Fees::parse(&gas, multiplier, price, &denom)
+        }
+        /// Nothing was broadcast, so there's no tx, gas, fee or result to report
+        fn reused(&mut self) -> anyhow::Result<()> {
+            self.populate_output("tx", ValueType::Null)?;
+            self.populate_output("gas_used", ValueType::Null)?;
+            self.populate_output("fee", ValueType::Null)?;
+            self.populate_output("result", ValueType::Null)?;
+            Ok(())
+        }
+        /// Effectful nodes end here, a dry run signs and simulates the tx instead of
+        /// broadcasting it. Returns the would-be result of the msg.
+        fn broadcast(&mut self, mut tx: Value, fees: &Fees) -> anyhow::Result<Value> {
+            if !self.env.dry_run {
+                anyhow::bail!("Broadcasting txs is not supported yet, try a dry run");
+            }
+
+            // Outputs the simulation doesn't know about are left empty
+            let graph = self.graph;
+            for (_, output_id) in graph[self.node_id].outputs.iter() {
+                self.outputs_cache
+                    .insert(*output_id, graph[*output_id].typ.default_value());
+            }
+
+            // With auto gas the limit and fee are only known after simulating,
+            // so the tx is signed again with them like a wallet would
+            let chain = self.env.chain;
+            tx["fee"] = fees.to_json(None)?;
+            let simulation = chain.simulate(&chain.sign(tx.clone())?)?;
+            let gas_used = simulation.gas_used;
+            if fees.limit(gas_used) < gas_used {
+                anyhow::bail!(
+                    "Out of gas, the tx uses {} but the limit is {}",
+                    gas_used,
+                    fees.limit(gas_used)
+                );
+            }
+            tx["fee"] = fees.to_json(Some(gas_used))?;
+            let signed = chain.sign(tx)?;
+
+            self.populate_output("gas_used", ValueType::Number(Number::from(gas_used)))?;
+            self.populate_output("fee", ValueType::Coin(fees.fee(gas_used)?))?;
+            self.populate_output("tx", ValueType::Json(signed.to_json()))?;
+            self.populate_output("result", ValueType::Json(simulation.result.clone()))?;
+            Ok(simulation.result)
+        }
     }
 
     let node = &graph[node_id];
-    let mut evaluator = Evaluator::new(graph, outputs_cache, node_id);
-    match node.user_data.template {
+    let mut evaluator = Evaluator::new(graph, outputs_cache, node_id, env);
+    let strict = node.user_data.strict;
+    match &node.user_data.template {
         // TODO: finish
         Template::MakeBool => {
             let bool = evaluator.evaluate_input("bool")?.try_into()?;
@@ -81,6 +200,56 @@ pub fn evaluate_node(
                 .populate_output("out", ValueType::String(string))?
                 .try_into()?])
         }
+        Template::MakeCoin => {
+            let amount: String = evaluator.evaluate_input("amount")?.try_into()?;
+            let denom: String = evaluator.evaluate_input("denom")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Coin(Coin::new(amount, denom)?))?
+                .try_into()?])
+        }
+        Template::Math(op) => {
+            let a: Number = evaluator.evaluate_input("a")?.try_into()?;
+            let b: Number = evaluator.evaluate_input("b")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Number(op.apply(&a, &b)?))?
+                .try_into()?])
+        }
+        Template::Compare(op) => {
+            let a: Number = evaluator.evaluate_input("a")?.try_into()?;
+            let b: Number = evaluator.evaluate_input("b")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Bool(op.apply(&a, &b)?))?
+                .try_into()?])
+        }
+        Template::And | Template::Or => {
+            let a: bool = evaluator.evaluate_input("a")?.try_into()?;
+            let out = match node.user_data.template {
+                Template::And => a && evaluator.evaluate_input("b")?.try_into()?,
+                _ => a || evaluator.evaluate_input("b")?.try_into()?,
+            };
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Bool(out))?
+                .try_into()?])
+        }
+        Template::Not => {
+            let bool: bool = evaluator.evaluate_input("bool")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Bool(!bool))?
+                .try_into()?])
+        }
+        Template::Select(_) => {
+            let cond: bool = evaluator.evaluate_input("cond")?.try_into()?;
+            // Only the taken branch is evaluated
+            let value = evaluator.evaluate_input(if cond { "then" } else { "else" })?;
+            Ok(vec![evaluator.populate_output("out", value)?.try_into()?])
+        }
+        Template::FormatString => {
+            let template: String = evaluator.evaluate_input(TEMPLATE_INPUT)?.try_into()?;
+            let string = interpolate(&template, |name| evaluator.evaluate_input(name)?.try_into())?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::String(string))?
+                .try_into()?])
+        }
         Template::ConstructJson => {
             let mut raw_json = Map::new();
             for (input, _) in evaluator.graph[node_id].inputs.iter() {
@@ -98,29 +267,92 @@ pub fn evaluate_node(
 
             for (name, id) in evaluator.graph[node_id].outputs.iter() {
                 // Check the output type
-                let output = match evaluator.graph.outputs.get(*id).unwrap().typ {
-                    DataType::Bool => ValueType::Bool(input[name].as_bool().unwrap_or_default()),
-                    DataType::Number => {
-                        let number = match input[name].clone() {
-                            Value::Number(n) => n,
-                            _ => Number::from(0),
-                        };
-                        ValueType::Number(number)
-                    }
-                    DataType::String => {
-                        ValueType::String(input[name].as_str().unwrap_or_default().to_string())
-                    }
-                    DataType::Array => {
-                        ValueType::Array(input[name].as_array().unwrap_or(&Vec::new()).clone())
-                    }
-                    DataType::Json => ValueType::Json(input[name].clone()),
-                };
+                let typ = &evaluator.graph.outputs.get(*id).unwrap().typ;
+                let output = ValueType::from_json(input.get(name), typ, strict)
+                    .map_err(|err| anyhow!("{}: {}", name, err))?;
 
                 res.push(evaluator.populate_output(&name, output)?.try_into()?);
             }
 
             Ok(res)
         }
+        Template::Extract(data) => {
+            let json: Value = evaluator.evaluate_input("json")?.try_into()?;
+            let path: String = evaluator.evaluate_input("path")?.try_into()?;
+
+            let value = select(&json, &path)?;
+            let out = ValueType::from_json(Some(&value), data, strict)?;
+            Ok(vec![evaluator.populate_output("out", out)?.try_into()?])
+        }
+        Template::MergeJson => {
+            let mut json: Value = evaluator.evaluate_input("base")?.try_into()?;
+            merge(&mut json, evaluator.evaluate_input("overlay")?.try_into()?);
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Json(json))?
+                .try_into()?])
+        }
+        Template::PatchJson => {
+            let json: Value = evaluator.evaluate_input("json")?.try_into()?;
+            let patch: Value = evaluator.evaluate_input("patch")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Json(apply(&json, &patch)?))?
+                .try_into()?])
+        }
+        Template::SetJson => {
+            let mut json: Value = evaluator.evaluate_input("json")?.try_into()?;
+            let path: String = evaluator.evaluate_input("path")?.try_into()?;
+            set(
+                &mut json,
+                &path,
+                evaluator.evaluate_input("value")?.try_into()?,
+            )?;
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Json(json))?
+                .try_into()?])
+        }
+        Template::Convert(_, to) => {
+            let value = evaluator.evaluate_input("value")?.coerce(to)?;
+            Ok(vec![evaluator.populate_output("out", value)?.try_into()?])
+        }
+        Template::JsonToBinary => {
+            let json: Value = evaluator.evaluate_input("json")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("binary", ValueType::Binary(serde_json::to_vec(&json)?))?
+                .try_into()?])
+        }
+        Template::BinaryToJson => {
+            let binary: Vec<u8> = evaluator.evaluate_input("binary")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("json", ValueType::Json(serde_json::from_slice(&binary)?))?
+                .try_into()?])
+        }
+        Template::StringToBinary => {
+            let string: String = evaluator.evaluate_input("string")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("binary", ValueType::Binary(string.into_bytes()))?
+                .try_into()?])
+        }
+        Template::BinaryToString => {
+            let binary: Vec<u8> = evaluator.evaluate_input("binary")?.try_into()?;
+            let string = String::from_utf8(binary).map_err(|_| anyhow!("Binary is not utf8"))?;
+            Ok(vec![evaluator
+                .populate_output("string", ValueType::String(string))?
+                .try_into()?])
+        }
+        Template::HexToBinary => {
+            let hex: String = evaluator.evaluate_input("hex")?.try_into()?;
+            let binary = hex::decode(hex.trim_start_matches("0x"))
+                .map_err(|err| anyhow!("Invalid hex: {}", err))?;
+            Ok(vec![evaluator
+                .populate_output("binary", ValueType::Binary(binary))?
+                .try_into()?])
+        }
+        Template::BinaryToHex => {
+            let binary: Vec<u8> = evaluator.evaluate_input("binary")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("hex", ValueType::String(hex::encode(binary)))?
+                .try_into()?])
+        }
         Template::ConstructArray(_) => {
             let mut arr: Vec<Value> = vec![];
 
@@ -138,25 +370,8 @@ pub fn evaluate_node(
             let mut res: Vec<String> = vec![];
 
             for i in 0..evaluator.graph[node_id].outputs.len() {
-                let value = arr.get(i).unwrap_or(&Value::Null);
-
-                let data = match data {
-                    DataType::Bool => ValueType::Bool(value.as_bool().unwrap_or_default()),
-                    DataType::Number => {
-                        let number = match value.clone() {
-                            Value::Number(n) => n,
-                            _ => Number::from(0),
-                        };
-                        ValueType::Number(number)
-                    }
-                    DataType::String => {
-                        ValueType::String(value.as_str().unwrap_or_default().to_string())
-                    }
-                    DataType::Array => {
-                        ValueType::Array(value.as_array().unwrap_or(&vec![]).clone())
-                    }
-                    DataType::Json => ValueType::Json(value.clone()),
-                };
+                let data = ValueType::from_json(arr.get(i), data, strict)
+                    .map_err(|err| anyhow!("{}: {}", i, err))?;
                 res.push(
                     evaluator
                         .populate_output(&(i).to_string(), data)?
@@ -166,12 +381,342 @@ pub fn evaluate_node(
 
             Ok(res)
         }
+        Template::ArrayLength(_) => {
+            let array: Vec<Value> = evaluator.evaluate_input("array")?.try_into()?;
+            Ok(vec![evaluator
+                .populate_output("length", ValueType::Number(Number::from(array.len())))?
+                .try_into()?])
+        }
+        Template::ArrayGet(data) => {
+            let array: Vec<Value> = evaluator.evaluate_input("array")?.try_into()?;
+            let index: Number = evaluator.evaluate_input("index")?.try_into()?;
+            let element = position(&index, array.len())
+                .and_then(|i| array.get(i))
+                .ok_or_else(|| anyhow!("Index {} is out of bounds", index))?;
+            let out = ValueType::from_json(Some(element), data, strict)?;
+            Ok(vec![evaluator.populate_output("out", out)?.try_into()?])
+        }
+        Template::ArrayConcat(_) => {
+            let mut array: Vec<Value> = evaluator.evaluate_input("a")?.try_into()?;
+            let b: Vec<Value> = evaluator.evaluate_input("b")?.try_into()?;
+            array.extend(b);
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Array(array))?
+                .try_into()?])
+        }
+        Template::ArraySlice(_) => {
+            let array: Vec<Value> = evaluator.evaluate_input("array")?.try_into()?;
+            let len = array.len();
+            let start = slice_bound(evaluator.evaluate_input("start")?.try_into()?, len)?;
+            let end = match evaluator.evaluate_input("end")? {
+                ValueType::Null => len,
+                end => slice_bound(end.try_into()?, len)?,
+            };
+            let slice = array.get(start..end.max(start)).unwrap_or_default();
+            Ok(vec![evaluator
+                .populate_output("out", ValueType::Array(slice.to_vec()))?
+                .try_into()?])
+        }
+        Template::ForEach(from, _) => {
+            let array: Vec<Value> = evaluator.evaluate_input("array")?.try_into()?;
+            let element_id = node.get_output("element")?;
+            let index_id = node.get_output("index")?;
+
+            let mut out = vec![];
+            for (i, element) in for_each_elements(&array, from, strict)?
+                .into_iter()
+                .enumerate()
+            {
+                // Each element gets its own cache so the loop body is evaluated again
+                let mut cache = evaluator.outputs_cache.clone();
+                cache.insert(element_id, element);
+                cache.insert(index_id, ValueType::Number(Number::from(i)));
+                let result = evaluate_input(graph, node_id, "result", &mut cache, env)
+                    .map_err(|err| anyhow!("{}: {}", i, err))?;
+                out.push(result.try_into()?);
+            }
+
+            populate_for_each(graph, evaluator.outputs_cache, node_id, from, out)
+        }
+        Template::WasmChecksum => {
+            let file: String = evaluator.evaluate_input("file")?.try_into()?;
+            // Nothing here knows the chain's own limit, the default is still plenty for a hash
+            let wasm = evaluator
+                .env
+                .wasm
+                .read(Path::new(&file), DEFAULT_MAX_WASM_SIZE)?;
+
+            evaluator.populate_output("size", ValueType::Number(Number::from(wasm.size)))?;
+            evaluator.populate_output("gzipped", ValueType::Bool(wasm.gzipped))?;
+            Ok(vec![evaluator
+                .populate_output("hash", ValueType::String(wasm.code_hash.clone()))?
+                .try_into()?])
+        }
+        Template::Subgraph(subgraph) => {
+            // Rebuilt on every evaluation so nothing leaks between uses
+            let mut inner = NodeGraph::new();
+            let ids = subgraph
+                .fragment
+                .insert(&mut inner, &mut GraphState::default());
+            // Ports are loaded from the saved subgraphs and can point past its nodes
+            let node = |port: &Port| {
+                ids.get(port.node).copied().ok_or_else(|| {
+                    anyhow!("{}: {} has no node to connect to", subgraph.name, port.name)
+                })
+            };
+
+            for port in subgraph.inputs.iter() {
+                let input = inner[node(port)?].get_input(&port.param)?;
+                inner[input].value = evaluator.evaluate_input(&port.name)?;
+            }
+
+            let mut cache = OutputsCache::new();
+            let mut res = vec![];
+            for port in subgraph.outputs.iter() {
+                let id = node(port)?;
+                let output = inner[id].get_output(&port.param)?;
+                if !cache.contains_key(&output) {
+                    evaluate_node(&inner, id, &mut cache, env)
+                        .map_err(|err| anyhow!("{}: {}", subgraph.name, err))?;
+                }
+                let value = cache
+                    .get(&output)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("{} was not evaluated", port.name))?;
+                res.push(evaluator.populate_output(&port.name, value)?.try_into()?);
+            }
+
+            Ok(res)
+        }
+        // Only builds the manifest, it's written to the file input on demand
+        Template::DeploymentOutput => {
+            let mut manifest = Manifest::default();
+            for (input, _) in evaluator.graph[node_id].inputs.iter() {
+                if input == "file" {
+                    continue;
+                }
+                manifest.add(input, evaluator.evaluate_input(input)?)?;
+            }
+
+            Ok(vec![evaluator
+                .populate_output("manifest", ValueType::Json(manifest.to_json()))?
+                .try_into()?])
+        }
+        Template::Store => {
+            let file: String = evaluator.evaluate_input("file")?.try_into()?;
+            let max_size: Number = evaluator.evaluate_input("max_size")?.try_into()?;
+            let max_size = max_size
+                .as_u64()
+                .ok_or_else(|| anyhow!("Max size must be a byte count, not {}", max_size))?;
+            let wasm = evaluator
+                .env
+                .wasm
+                .read(Path::new(&file), max_size as usize)?;
+            wasm.validate(max_size as usize)?;
+            let hash = wasm.code_hash.clone();
+            let fees = evaluator.evaluate_fees()?;
+
+            // Reuse the code id if this exact wasm was already stored
+            match evaluator
+                .env
+                .deployment
+                .and_then(|deployment| deployment.find_code(&hash))
+            {
+                Some(id) => {
+                    evaluator.reused()?;
+                    let code = json!({ "code_id": id, "code_hash": hash });
+                    evaluator.populate_output("code", ValueType::Json(code))?;
+                    Ok(vec![evaluator
+                        .populate_output("id", ValueType::Number(id))?
+                        .try_into()?])
+                }
+                None => {
+                    let tx = json!({
+                        "store_code": {
+                            "file": file,
+                            "code_hash": hash,
+                            "size": wasm.size,
+                        }
+                    });
+                    let result = evaluator.broadcast(tx, &fees)?;
+                    // The hash is known before storing, the id only if the chain reports it
+                    let id = result["code_id"].clone();
+                    if let Value::Number(id) = &id {
+                        evaluator.populate_output("id", ValueType::Number(id.clone()))?;
+                    }
+                    let code = json!({ "code_id": id, "code_hash": hash });
+                    Ok(vec![evaluator
+                        .populate_output("code", ValueType::Json(code))?
+                        .try_into()?])
+                }
+            }
+        }
+        Template::Instantiate => {
+            let code_id: Number = evaluator.evaluate_input("id")?.try_into()?;
+            let msg: Value = match &node.user_data.variant {
+                Some(variant) => evaluator.evaluate_msg(variant, "msg.")?,
+                None => evaluator.evaluate_input("msg")?.try_into()?,
+            };
+            if let Some(schema) = &node.user_data.schema {
+                schema.validate(MsgKind::Instantiate, &msg)?;
+            }
+
+            let label: String = evaluator.evaluate_input("label")?.try_into()?;
+            let funds = evaluator.evaluate_funds()?;
+            let fees = evaluator.evaluate_fees()?;
+
+            // Labels are unique, so a matching one means it's already instantiated
+            match evaluator
+                .env
+                .deployment
+                .and_then(|deployment| deployment.find_contract(&label))
+            {
+                Some(contract) => {
+                    evaluator.reused()?;
+                    Ok(vec![evaluator
+                        .populate_output("contract", ValueType::Json(contract))?
+                        .try_into()?])
+                }
+                None => {
+                    let tx = json!({
+                        "instantiate_contract": {
+                            "code_id": code_id,
+                            "label": label,
+                            "msg": msg,
+                            "funds": funds,
+                        }
+                    });
+                    let result = evaluator.broadcast(tx, &fees)?;
+                    // Null unless the chain reports the address, the deployment rejects it then
+                    let contract = json!({
+                        "address": result["address"],
+                        "code_hash": null,
+                        "label": label,
+                        "code_id": code_id,
+                    });
+                    Ok(vec![evaluator
+                        .populate_output("contract", ValueType::Json(contract))?
+                        .try_into()?])
+                }
+            }
+        }
+        Template::ConstructMsg => {
+            let msg = match &node.user_data.variant {
+                Some(variant) => evaluator.evaluate_msg(variant, "")?,
+                None => {
+                    let typ: String = evaluator.evaluate_input("type")?.try_into()?;
+                    let mut msg = Map::new();
+                    msg.insert(typ, evaluator.evaluate_input("json")?.try_into()?);
+                    Value::Object(msg)
+                }
+            };
+
+            if let Some(schema) = &node.user_data.schema {
+                match &node.user_data.variant {
+                    Some(variant) => schema.validate(variant.kind, &msg)?,
+                    // Raw msgs can be either
+                    None => schema
+                        .validate(MsgKind::Execute, &msg)
+                        .or_else(|_| schema.validate(MsgKind::Query, &msg))?,
+                }
+            }
+
+            Ok(vec![evaluator
+                .populate_output("msg", ValueType::Json(msg))?
+                .try_into()?])
+        }
+        Template::ExecuteContract => {
+            let contract: Value = evaluator.evaluate_input("contract")?.try_into()?;
+            let msg: Value = evaluator.evaluate_input("msg")?.try_into()?;
+            let funds = evaluator.evaluate_funds()?;
+            let fees = evaluator.evaluate_fees()?;
+            let result = evaluator.broadcast(
+                json!({
+                    "execute_contract": {
+                        "contract": contract["address"],
+                        "msg": msg,
+                        "funds": funds,
+                    }
+                }),
+                &fees,
+            )?;
+            Ok(vec![ValueType::Json(result).try_into()?])
+        }
+        Template::ContractInterface => {
+            let schema = node
+                .user_data
+                .schema
+                .as_ref()
+                .ok_or_else(|| anyhow!("No schema loaded"))?;
+
+            let mut json = Map::new();
+            for (kind, root) in [
+                ("instantiate", &schema.instantiate),
+                ("execute", &schema.execute),
+                ("query", &schema.query),
+            ] {
+                json.insert(kind.to_string(), root.clone().unwrap_or(Value::Null));
+            }
+            json.insert(
+                "responses".to_string(),
+                Value::Object(schema.responses.clone()),
+            );
+
+            Ok(vec![evaluator
+                .populate_output("schema", ValueType::Json(Value::Object(json)))?
+                .try_into()?])
+        }
+        Template::ContractCall(_, variant) => {
+            let msg = evaluator.evaluate_msg(variant, "msg.")?;
+            if let Some(schema) = &node.user_data.schema {
+                schema.validate(variant.kind, &msg)?;
+            }
+
+            match variant.kind {
+                MsgKind::Query => anyhow::bail!("Querying contracts is not supported yet"),
+                _ => {
+                    let contract: Value = evaluator.evaluate_input("contract")?.try_into()?;
+                    let funds = evaluator.evaluate_funds()?;
+                    let fees = evaluator.evaluate_fees()?;
+                    let result = evaluator.broadcast(
+                        json!({
+                            "execute_contract": {
+                                "contract": contract["address"],
+                                "msg": msg,
+                                "funds": funds,
+                            }
+                        }),
+                        &fees,
+                    )?;
+                    Ok(vec![ValueType::Json(result).try_into()?])
+                }
+            }
+        }
+        // TODO: Account, QueryContract and ContractCall queries need a client for a real chain,
+        //  txs can only be simulated and Store and Instantiate reuse a previous deployment for now
         _ => Ok(vec![evaluator
             .populate_output("out", ValueType::Bool(false))?
             .try_into()?]),
     }
 }
 
+/// Array position of an index, negative ones count from the end
+fn position(index: &Number, len: usize) -> Option<usize> {
+    let index = index.as_i64()?;
+    let index = if index < 0 { len as i64 + index } else { index };
+    usize::try_from(index).ok()
+}
+
+/// Like `position` but out of range indexes are clamped to the array, only non integers error
+fn slice_bound(index: Number, len: usize) -> anyhow::Result<usize> {
+    match (index.as_i64(), index.as_u64()) {
+        (Some(_), _) => Ok(position(&index, len).unwrap_or(0).min(len)),
+        // Too big for an i64, so past the end
+        (None, Some(_)) => Ok(len),
+        (None, None) => anyhow::bail!("Index {} is not an integer", index),
+    }
+}
+
 fn populate_output(
     graph: &NodeGraph,
     outputs_cache: &mut OutputsCache,
@@ -186,11 +731,12 @@ fn populate_output(
 }
 
 // Evaluates the input value of
-fn evaluate_input(
+pub fn evaluate_input(
     graph: &NodeGraph,
     node_id: NodeId,
     param_name: &str,
     outputs_cache: &mut OutputsCache,
+    env: Environment,
 ) -> anyhow::Result<ValueType> {
     let input_id = graph[node_id].get_input(param_name)?;
 
@@ -205,7 +751,7 @@ fn evaluate_input(
         // recursively evaluate it.
         else {
             // Calling this will populate the cache
-            evaluate_node(graph, graph[other_output_id].node, outputs_cache)?;
+            evaluate_node(graph, graph[other_output_id].node, outputs_cache, env)?;
 
             // Now that we know the value is cached, return it
             Ok(outputs_cache
@@ -216,6 +762,108 @@ fn evaluate_input(
     }
     // No existing connection, take the inline value instead.
     else {
-        Ok(graph[input_id].value.clone())
+        Ok(graph[input_id].value.clone().resolved())
     }
 }
+
+/// Elements a For Each node loops over, typed like its element output
+pub fn for_each_elements(
+    array: &[Value],
+    from: &DataType,
+    strict: bool,
+) -> anyhow::Result<Vec<ValueType>> {
+    array
+        .iter()
+        .enumerate()
+        .map(|(i, element)| {
+            ValueType::from_json(Some(element), from, strict)
+                .map_err(|err| anyhow!("{}: {}", i, err))
+        })
+        .collect()
+}
+
+/// Fills a For Each node's outputs with the results of its body
+pub fn populate_for_each(
+    graph: &NodeGraph,
+    outputs_cache: &mut OutputsCache,
+    node_id: NodeId,
+    from: &DataType,
+    out: Vec<Value>,
+) -> anyhow::Result<Vec<String>> {
+    // Outside the loop they hold the defaults
+    populate_output(
+        graph,
+        outputs_cache,
+        node_id,
+        "element",
+        from.default_value(),
+    )?;
+    populate_output(
+        graph,
+        outputs_cache,
+        node_id,
+        "index",
+        ValueType::Number(Number::from(0)),
+    )?;
+    Ok(vec![populate_output(
+        graph,
+        outputs_cache,
+        node_id,
+        "out",
+        ValueType::Array(out),
+    )?
+    .try_into()?])
+}
+
+/// Lists every node needed to evaluate `node_id`, dependencies first and the node itself last.
+/// Follows the same connections `evaluate_input` recurses through, except for lazy inputs
+/// which are evaluated by their node when it needs them.
+pub fn evaluation_order(graph: &NodeGraph, node_id: NodeId) -> Vec<NodeId> {
+    fn visit(
+        graph: &NodeGraph,
+        node_id: NodeId,
+        visited: &mut HashSet<NodeId>,
+        order: &mut Vec<NodeId>,
+    ) {
+        // Also stops us from going around in circles
+        if !visited.insert(node_id) {
+            return;
+        }
+
+        let lazy = graph[node_id].user_data.template.lazy_inputs();
+        for (name, input_id) in graph[node_id].inputs.iter() {
+            if lazy.contains(&name.as_str()) {
+                continue;
+            }
+            if let Some(output_id) = graph.connection(*input_id) {
+                visit(graph, graph[output_id].node, visited, order);
+            }
+        }
+
+        order.push(node_id);
+    }
+
+    let mut order = vec![];
+    visit(graph, node_id, &mut HashSet::new(), &mut order);
+    order
+}
+
+/// Resolves the node inputs without evaluating anything,
+/// connected inputs that haven't been computed yet return None
+pub fn peek_inputs(
+    graph: &NodeGraph,
+    node_id: NodeId,
+    outputs_cache: &OutputsCache,
+) -> Vec<(String, Option<ValueType>)> {
+    graph[node_id]
+        .inputs
+        .iter()
+        .map(|(name, input_id)| {
+            let value = match graph.connection(*input_id) {
+                Some(output_id) => outputs_cache.get(&output_id).cloned(),
+                None => Some(graph[*input_id].value.clone().resolved()),
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+use crate::node::data::Coin;
+use anyhow::anyhow;
+use serde_json::{json, Value};
+
+/// Gas and fee settings of a tx, read from the fee inputs
+pub struct Fees {
+    /// Gas limit, `None` is `auto` which applies the multiplier to the simulated gas
+    pub gas: Option<u64>,
+    pub multiplier: f64,
+    /// Price per unit of gas
+    pub price: f64,
+    pub denom: String,
+}
+
+impl Fees {
+    pub fn parse(gas: &str, multiplier: f64, price: f64, denom: &str) -> anyhow::Result<Self> {
+        let gas = match gas.trim() {
+            "auto" => None,
+            limit => Some(
+                limit
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("Gas must be a limit or auto, not {}", limit))?,
+            ),
+        };
+
+        let decimal = |name: &str, value: f64| {
+            if value.is_finite() && value >= 0.0 {
+                Ok(value)
+            } else {
+                Err(anyhow!(
+                    "{} must be a positive decimal, not {}",
+                    name,
+                    value
+                ))
+            }
+        };
+        let multiplier = decimal("Gas multiplier", multiplier)?;
+        if multiplier == 0.0 {
+            anyhow::bail!("Gas multiplier cannot be 0");
+        }
+        let price = decimal("Gas price", price)?;
+
+        let denom = denom.trim();
+        if denom.is_empty() {
+            anyhow::bail!("Fee denom cannot be empty");
+        }
+
+        Ok(Self {
+            gas,
+            multiplier,
+            price,
+            denom: denom.to_string(),
+        })
+    }
+
+    /// Gas limit for the simulated gas, a set limit is used as is
+    pub fn limit(&self, gas_used: u64) -> u64 {
+        self.gas
+            .unwrap_or_else(|| (gas_used as f64 * self.multiplier).ceil() as u64)
+    }
+
+    /// Fee paid for the gas limit
+    pub fn fee(&self, gas_used: u64) -> anyhow::Result<Coin> {
+        let amount = (self.limit(gas_used) as f64 * self.price).ceil() as u128;
+        Coin::new(amount.to_string(), self.denom.clone())
+    }
+
+    /// Fee section of the tx, the limit and fee are only known once simulated with `auto`
+    pub fn to_json(&self, gas_used: Option<u64>) -> anyhow::Result<Value> {
+        let (gas, fee) = match gas_used {
+            Some(gas_used) => {
+                let fee = self.fee(gas_used)?;
+                (
+                    Value::from(self.limit(gas_used)),
+                    json!({ "amount": fee.amount, "denom": fee.denom }),
+                )
+            }
+            None => (
+                self.gas.map(Value::from).unwrap_or_else(|| "auto".into()),
+                Value::Null,
+            ),
+        };
+
+        Ok(json!({
+            "gas": gas,
+            "gas_multiplier": self.multiplier,
+            "gas_price": self.price,
+            "fee_denom": self.denom,
+            "fee": fee,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_is_a_limit_or_auto() {
+        assert_eq!(Fees::parse("auto", 1.3, 0.25, "uscrt").unwrap().gas, None);
+        assert_eq!(
+            Fees::parse(" 200000 ", 1.3, 0.25, "uscrt").unwrap().gas,
+            Some(200000)
+        );
+        assert!(Fees::parse("lots", 1.3, 0.25, "uscrt").is_err());
+        assert!(Fees::parse("-1", 1.3, 0.25, "uscrt").is_err());
+    }
+
+    #[test]
+    fn decimals_and_denom_are_validated() {
+        assert!(Fees::parse("auto", 0.0, 0.25, "uscrt").is_err());
+        assert!(Fees::parse("auto", 1.3, -0.25, "uscrt").is_err());
+        assert!(Fees::parse("auto", 1.3, f64::NAN, "uscrt").is_err());
+        assert!(Fees::parse("auto", 1.3, 0.25, " ").is_err());
+    }
+
+    #[test]
+    fn auto_applies_the_multiplier() {
+        let auto = Fees::parse("auto", 1.3, 0.25, "uscrt").unwrap();
+        assert_eq!(auto.limit(100000), 130000);
+        assert_eq!(
+            auto.fee(100000).unwrap(),
+            Coin::new("32500".to_string(), "uscrt".to_string()).unwrap()
+        );
+        assert_eq!(auto.to_json(None).unwrap()["gas"], json!("auto"));
+        assert_eq!(auto.to_json(None).unwrap()["fee"], Value::Null);
+        assert_eq!(auto.to_json(Some(100000)).unwrap()["gas"], json!(130000));
+    }
+
+    #[test]
+    fn set_limits_ignore_the_multiplier_and_round_up() {
+        let fees = Fees::parse("100001", 1.3, 0.25, "uscrt").unwrap();
+        assert_eq!(fees.limit(5), 100001);
+        assert_eq!(
+            fees.fee(5).unwrap(),
+            Coin::new("25001".to_string(), "uscrt".to_string()).unwrap()
+        );
+        assert_eq!(
+            fees.to_json(Some(5)).unwrap()["fee"],
+            json!({ "amount": "25001", "denom": "uscrt" })
+        );
+    }
+}
@@ -0,0 +1,128 @@
+/// The string template node's own input, it can't be a placeholder too
+pub const TEMPLATE_INPUT: &str = "template";
+
+/// Unique `{{name}}` placeholders in order of appearance
+pub fn placeholders(template: &str) -> anyhow::Result<Vec<String>> {
+    let mut names: Vec<String> = vec![];
+    let mut rest = template;
+    while let Some((_, name, after)) = next_placeholder(rest) {
+        let name = check_name(name)?;
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+        rest = after;
+    }
+    Ok(names)
+}
+
+/// Replaces every placeholder with its value
+pub fn interpolate(
+    template: &str,
+    mut value: impl FnMut(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some((before, name, after)) = next_placeholder(rest) {
+        let name = check_name(name)?;
+        if name == TEMPLATE_INPUT {
+            anyhow::bail!(
+                "{{{{{}}}}} is reserved for the template input, rename the placeholder",
+                TEMPLATE_INPUT
+            );
+        }
+        out.push_str(before);
+        out.push_str(&value(name)?);
+        rest = after;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Names become input ports, braces in them are always a typo like `{{{name}}}`
+fn check_name(name: &str) -> anyhow::Result<&str> {
+    if name.contains(['{', '}']) {
+        anyhow::bail!(
+            "Invalid placeholder {{{{{}}}}}, names can't contain braces",
+            name
+        );
+    }
+    Ok(name)
+}
+
+/// The text before the next placeholder, its trimmed name and the text after it
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let mut from = 0;
+    loop {
+        let start = text[from..].find("{{")? + from;
+        let end = text[start..].find("}}")? + start;
+        let name = text[start + 2..end].trim();
+        if !name.is_empty() {
+            return Some((&text[..start], name, &text[end + 2..]));
+        }
+        // Empty braces are kept as is
+        from = end + 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn values(name: &str) -> anyhow::Result<String> {
+        match name {
+            "prefix" => Ok("snip".to_string()),
+            "version" => Ok("2".to_string()),
+            name => Err(anyhow!("Missing input {}", name)),
+        }
+    }
+
+    #[test]
+    fn placeholders_are_unique_and_trimmed() {
+        let names = placeholders("{{prefix}}-token-{{ version }}-{{prefix}}").unwrap();
+        assert_eq!(names, vec!["prefix", "version"]);
+        assert!(placeholders("no placeholders").unwrap().is_empty());
+    }
+
+    #[test]
+    fn placeholders_are_replaced() {
+        let label = interpolate("{{prefix}}-token-{{ version }}", values).unwrap();
+        assert_eq!(label, "snip-token-2");
+    }
+
+    #[test]
+    fn empty_and_unclosed_braces_are_kept() {
+        assert!(placeholders("{{}} {{ }} {{prefix").unwrap().is_empty());
+        assert_eq!(
+            interpolate("{{}}{{ }}{{prefix}} {{version", values).unwrap(),
+            "{{}}{{ }}snip {{version"
+        );
+        // Single braces are plain text, like in json
+        assert_eq!(
+            interpolate(r#"{"label": "{{prefix}}"}"#, values).unwrap(),
+            r#"{"label": "snip"}"#
+        );
+    }
+
+    #[test]
+    fn names_with_braces_are_rejected() {
+        assert!(placeholders("{{{prefix}}}").is_err());
+        assert!(interpolate("{{{prefix}}}", values).is_err());
+        assert!(interpolate("{{a{{b}}", values).is_err());
+    }
+
+    #[test]
+    fn template_is_reserved() {
+        assert_eq!(placeholders("{{template}}").unwrap(), vec![TEMPLATE_INPUT]);
+        let err = interpolate("{{ template }}", values).unwrap_err();
+        assert!(err.to_string().contains("reserved"));
+    }
+
+    #[test]
+    fn missing_inputs_error_and_extra_ones_are_unused() {
+        let err = interpolate("{{prefix}}-{{suffix}}", values).unwrap_err();
+        assert_eq!(err.to_string(), "Missing input suffix");
+        // Version has a value but no placeholder
+        assert_eq!(interpolate("{{prefix}}", values).unwrap(), "snip");
+    }
+}
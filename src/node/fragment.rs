@@ -0,0 +1,200 @@
+use crate::node::data::{DataType, ValueType};
+use crate::node::schema::{ContractSchema, Variant};
+use crate::node::state::NodeState;
+use crate::node::template::{add_param, Template, IO};
+use crate::node::{GraphState, NodeGraph};
+use eframe::egui;
+use egui_node_graph::{NodeId, NodeTemplateTrait};
+
+/// A self contained copy of some nodes, their inline values and the connections between them
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Fragment {
+    /// Position of the first node when it was copied
+    pub origin: [f32; 2],
+    pub nodes: Vec<FragmentNode>,
+    pub connections: Vec<Connection>,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FragmentNode {
+    pub template: Template,
+    pub strict: bool,
+    pub schema: Option<ContractSchema>,
+    pub variant: Option<Variant>,
+    /// Inputs with their inline values, recreated as is since ports can be added by hand
+    pub inputs: Vec<(String, DataType, ValueType)>,
+    pub outputs: Vec<(String, DataType)>,
+    /// Position relative to the first node
+    pub offset: [f32; 2],
+}
+
+/// Connects an output to an input, nodes are indexes into `Fragment::nodes`
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Connection {
+    pub output: (usize, String),
+    pub input: (usize, String),
+}
+
+impl Fragment {
+    /// Copies the nodes, connections to anything outside of them are left out
+    pub fn from_nodes(
+        graph: &NodeGraph,
+        nodes: &[NodeId],
+        position: impl Fn(NodeId) -> egui::Pos2,
+    ) -> Self {
+        let origin = nodes.first().map(|id| position(*id)).unwrap_or_default();
+
+        let fragment_nodes = nodes
+            .iter()
+            .map(|id| {
+                let node = &graph[*id];
+                let offset = position(*id) - origin;
+                FragmentNode {
+                    template: node.user_data.template.clone(),
+                    strict: node.user_data.strict,
+                    schema: node.user_data.schema.clone(),
+                    variant: node.user_data.variant.clone(),
+                    inputs: node
+                        .inputs
+                        .iter()
+                        .map(|(name, input)| {
+                            let input = &graph[*input];
+                            (name.clone(), input.typ.clone(), input.value.clone())
+                        })
+                        .collect(),
+                    outputs: node
+                        .outputs
+                        .iter()
+                        .map(|(name, output)| (name.clone(), graph[*output].typ.clone()))
+                        .collect(),
+                    offset: [offset.x, offset.y],
+                }
+            })
+            .collect();
+
+        let mut connections = vec![];
+        for (input_node, id) in nodes.iter().enumerate() {
+            for (input_name, input) in graph[*id].inputs.iter() {
+                let output = match graph.connection(*input) {
+                    Some(output) => output,
+                    None => continue,
+                };
+                let output_node = graph[output].node;
+                let output_index = match nodes.iter().position(|id| *id == output_node) {
+                    Some(index) => index,
+                    None => continue,
+                };
+                let output_name = graph[output_node]
+                    .outputs
+                    .iter()
+                    .find(|(_, id)| *id == output)
+                    .map(|(name, _)| name.clone());
+                if let Some(output_name) = output_name {
+                    connections.push(Connection {
+                        output: (output_index, output_name),
+                        input: (input_node, input_name.clone()),
+                    });
+                }
+            }
+        }
+
+        Self {
+            origin: [origin.x, origin.y],
+            nodes: fragment_nodes,
+            connections,
+        }
+    }
+
+    /// Adds the nodes with the ports they were copied with, returns their new ids in the same order
+    pub fn insert(&self, graph: &mut NodeGraph, user_state: &mut GraphState) -> Vec<NodeId> {
+        let ids: Vec<NodeId> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let user_data = NodeState {
+                    template: node.template.clone(),
+                    strict: node.strict,
+                    breakpoint: false,
+                    condition: String::new(),
+                    schema: node.schema.clone(),
+                    variant: node.variant.clone(),
+                };
+                let label = node.template.node_graph_label(user_state);
+                let id = graph.add_node(label, user_data, |graph, id| {
+                    for (name, data, value) in node.inputs.iter() {
+                        add_param(id, data.clone(), name, IO::Input, graph);
+                        if let Ok(input) = graph[id].get_input(name) {
+                            graph[input].value = value.clone();
+                        }
+                    }
+                    for (name, data) in node.outputs.iter() {
+                        add_param(id, data.clone(), name, IO::Output, graph);
+                    }
+                });
+                id
+            })
+            .collect();
+
+        // Pasted text can be anything, connections to missing nodes or params are skipped
+        for connection in self.connections.iter() {
+            let (output_node, output_name) = &connection.output;
+            let (input_node, input_name) = &connection.input;
+            let (output_node, input_node) = match (ids.get(*output_node), ids.get(*input_node)) {
+                (Some(output_node), Some(input_node)) => (*output_node, *input_node),
+                _ => continue,
+            };
+            let output = graph[output_node].get_output(output_name);
+            let input = graph[input_node].get_input(input_name);
+            if let (Ok(output), Ok(input)) = (output, input) {
+                graph.add_connection(output, input);
+            }
+        }
+
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports_and_connections_round_trip() {
+        let mut user_state = GraphState::default();
+        let mut graph = NodeGraph::new();
+        let mut add = |graph: &mut NodeGraph, template: Template| {
+            let label = template.node_graph_label(&mut user_state);
+            let user_data = template.user_data(&mut user_state);
+            graph.add_node(label, user_data, |graph, id| {
+                template.build_node(graph, &mut user_state, id)
+            })
+        };
+        let name = add(&mut graph, Template::MakeString);
+        let format = add(&mut graph, Template::FormatString);
+        // Placeholder inputs aren't part of the template, they must be copied as is
+        add_param(format, DataType::String, "name", IO::Input, &mut graph);
+        let input = graph[format].get_input("name").unwrap();
+        graph[input].value = ValueType::String("world".to_string());
+        let output = graph[name].get_output("out").unwrap();
+        graph.add_connection(output, input);
+
+        let fragment = Fragment::from_nodes(&graph, &[name, format], |_| egui::Pos2::default());
+        let text = serde_json::to_string(&fragment).unwrap();
+        let mut pasted: Fragment = serde_json::from_str(&text).unwrap();
+
+        let mut other = NodeGraph::new();
+        let ids = pasted.insert(&mut other, &mut GraphState::default());
+        let copy = Fragment::from_nodes(&other, &ids, |_| egui::Pos2::default());
+        assert!(copy == fragment);
+        assert!(other[ids[1]].get_input("name").is_ok());
+
+        // Stale indexes from the clipboard are skipped instead of panicking
+        pasted.connections.push(Connection {
+            output: (7, "out".to_string()),
+            input: (1, "name".to_string()),
+        });
+        let ids = pasted.insert(&mut other, &mut GraphState::default());
+        assert_eq!(ids.len(), 2);
+        assert_eq!(other.iter_connections().count(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::node::data::ValueType;
+use anyhow::anyhow;
+use serde_json::{json, Map, Number, Value};
+use std::path::Path;
+
+/// Deployment summary shared with downstream teams, keys are sorted so the file stays stable.
+///
+/// ```json
+/// {
+///   "codes": { "<name>": { "code_id": 1, "code_hash": "..." } },
+///   "contracts": { "<name>": { "address": "...", "code_hash": "...", "label": "...", "code_id": 1 } }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Manifest {
+    pub codes: Map<String, Value>,
+    pub contracts: Map<String, Value>,
+}
+
+impl Manifest {
+    /// Json with an address is stored as a contract and Json with a code id as a code.
+    /// Bare code ids are rejected, without their hash they can't be reused,
+    /// and so are the null placeholders of a dry run.
+    pub fn add(&mut self, name: &str, value: ValueType) -> anyhow::Result<()> {
+        match value {
+            ValueType::Number(_) => anyhow::bail!(
+                "{} is a bare code id, connect the Store code output to record its hash",
+                name
+            ),
+            ValueType::Json(Value::Object(object)) => {
+                let field = |key: &str| object.get(key).cloned().unwrap_or_default();
+
+                if object.contains_key("address") {
+                    if !object["address"].is_string() {
+                        anyhow::bail!("{} has no address, it was not instantiated", name);
+                    }
+                    self.contracts.insert(
+                        name.to_string(),
+                        json!({
+                            "address": field("address"),
+                            "code_hash": field("code_hash"),
+                            "label": field("label"),
+                            "code_id": field("code_id"),
+                        }),
+                    );
+                } else if object.contains_key("code_id") {
+                    if !object["code_id"].is_number() {
+                        anyhow::bail!("{} has no code id, it was not stored", name);
+                    }
+                    self.codes.insert(
+                        name.to_string(),
+                        json!({
+                            "code_id": field("code_id"),
+                            "code_hash": field("code_hash"),
+                        }),
+                    );
+                } else {
+                    anyhow::bail!("{} is neither a contract nor a code id", name);
+                }
+            }
+            _ => anyhow::bail!("Cannot add {:?} to the deployment as {}", value, name),
+        }
+
+        Ok(())
+    }
+
+    pub fn from_json(value: Value) -> anyhow::Result<Self> {
+        let section = |key: &str| match &value[key] {
+            Value::Object(object) => Ok(object.clone()),
+            Value::Null => Ok(Map::new()),
+            _ => Err(anyhow!("Expected {} to be an object", key)),
+        };
+
+        Ok(Self {
+            codes: section("codes")?,
+            contracts: section("contracts")?,
+        })
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| anyhow!("Could not read {}: {}", path.display(), err))?;
+        Self::from_json(serde_json::from_str(&content)?)
+    }
+
+    /// Code id of a previously stored wasm with the same code hash
+    pub fn find_code(&self, code_hash: &str) -> Option<Number> {
+        self.codes.values().find_map(
+            |code| match (code["code_hash"].as_str(), &code["code_id"]) {
+                (Some(hash), Value::Number(id)) if hash.eq_ignore_ascii_case(code_hash) => {
+                    Some(id.clone())
+                }
+                _ => None,
+            },
+        )
+    }
+
+    /// Recorded contract with the given label, entries without an address can't be reused
+    pub fn find_contract(&self, label: &str) -> Option<Value> {
+        self.contracts
+            .values()
+            .find(|contract| {
+                contract["label"].as_str() == Some(label) && contract["address"].is_string()
+            })
+            .cloned()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "codes": self.codes,
+            "contracts": self.contracts,
+        })
+    }
+
+    /// Checks every entry points at something on chain
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, contract) in self.contracts.iter() {
+            if !contract["address"].is_string() {
+                anyhow::bail!("{} has no address", name);
+            }
+        }
+        for (name, code) in self.codes.iter() {
+            if !code["code_id"].is_number() {
+                anyhow::bail!("{} has no code id", name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the manifest, skipping the write if nothing changed
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        self.validate()?;
+        let content = serde_json::to_string_pretty(&self.to_json())?;
+        if std::fs::read_to_string(path).ok().as_deref() != Some(content.as_str()) {
+            std::fs::write(path, content)
+                .map_err(|err| anyhow!("Could not write {}: {}", path.display(), err))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dry_run_placeholders_are_rejected() {
+        let mut manifest = Manifest::default();
+        let contract =
+            json!({ "address": null, "code_hash": null, "label": "token-v1", "code_id": null });
+        assert!(manifest.add("token", ValueType::Json(contract)).is_err());
+        let code = json!({ "code_id": null, "code_hash": "ab" });
+        assert!(manifest.add("code", ValueType::Json(code)).is_err());
+        assert!(manifest.contracts.is_empty() && manifest.codes.is_empty());
+    }
+
+    #[test]
+    fn only_contracts_with_an_address_are_reused() {
+        let manifest = Manifest::from_json(json!({
+            "codes": { "code": { "code_id": null, "code_hash": "ab" } },
+            "contracts": {
+                "token": { "address": null, "label": "token-v1" },
+                "other": { "address": "secret1abc", "label": "other-v1" },
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(manifest.find_contract("token-v1"), None);
+        assert_eq!(
+            manifest.find_contract("other-v1").unwrap()["address"],
+            json!("secret1abc")
+        );
+        assert_eq!(manifest.find_code("AB"), None);
+    }
+
+    #[test]
+    fn placeholders_are_not_written() {
+        let manifest = Manifest::from_json(json!({
+            "contracts": { "token": { "address": null, "label": "token-v1" } }
+        }))
+        .unwrap();
+        let path = std::env::temp_dir().join("manifest-placeholders.json");
+        assert!(manifest.write(&path).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn deployed_entries_round_trip() {
+        let mut manifest = Manifest::default();
+        let contract = json!({ "address": "secret1abc", "code_hash": "ab", "label": "token-v1", "code_id": 1 });
+        manifest.add("token", ValueType::Json(contract)).unwrap();
+        manifest
+            .add(
+                "code",
+                ValueType::Json(json!({ "code_id": 1, "code_hash": "ab" })),
+            )
+            .unwrap();
+        assert!(manifest
+            .add("id", ValueType::Number(Number::from(1)))
+            .is_err());
+
+        let manifest = Manifest::from_json(manifest.to_json()).unwrap();
+        assert_eq!(manifest.find_code("AB"), Some(Number::from(1)));
+        assert!(manifest.find_contract("token-v1").is_some());
+    }
+}
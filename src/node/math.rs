@@ -0,0 +1,204 @@
+use anyhow::anyhow;
+use num_bigint::BigInt;
+use serde_json::Number;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum MathOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Min,
+    Max,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Lt,
+    Gt,
+}
+
+/// Integers are kept as big ints so token amounts past u64 don't lose precision
+enum Operand {
+    Int(BigInt),
+    Float(f64),
+}
+
+impl Operand {
+    fn new(number: &Number) -> anyhow::Result<Self> {
+        let text = number.to_string();
+        if text.contains(|c| c == '.' || c == 'e' || c == 'E') {
+            Ok(Operand::Float(text.parse()?))
+        } else {
+            Ok(Operand::Int(BigInt::from_str(&text)?))
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            Operand::Int(int) => int.to_string().parse().unwrap_or(f64::NAN),
+            Operand::Float(float) => *float,
+        }
+    }
+}
+
+impl MathOp {
+    pub const ALL: [MathOp; 7] = [
+        MathOp::Add,
+        MathOp::Sub,
+        MathOp::Mul,
+        MathOp::Div,
+        MathOp::Mod,
+        MathOp::Min,
+        MathOp::Max,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MathOp::Add => "Add",
+            MathOp::Sub => "Subtract",
+            MathOp::Mul => "Multiply",
+            MathOp::Div => "Divide",
+            MathOp::Mod => "Modulo",
+            MathOp::Min => "Min",
+            MathOp::Max => "Max",
+        }
+    }
+
+    /// Integer division truncates like the contracts do
+    pub fn apply(&self, a: &Number, b: &Number) -> anyhow::Result<Number> {
+        match (Operand::new(a)?, Operand::new(b)?) {
+            (Operand::Int(a), Operand::Int(b)) => {
+                if b == BigInt::from(0) && matches!(self, MathOp::Div | MathOp::Mod) {
+                    anyhow::bail!("Division by zero");
+                }
+                let result = match self {
+                    MathOp::Add => a + b,
+                    MathOp::Sub => a - b,
+                    MathOp::Mul => a * b,
+                    MathOp::Div => a / b,
+                    MathOp::Mod => a % b,
+                    MathOp::Min => a.min(b),
+                    MathOp::Max => a.max(b),
+                };
+                Ok(Number::from_str(&result.to_string())?)
+            }
+            (a, b) => {
+                let (a, b) = (a.as_f64(), b.as_f64());
+                let result = match self {
+                    MathOp::Add => a + b,
+                    MathOp::Sub => a - b,
+                    MathOp::Mul => a * b,
+                    MathOp::Div => a / b,
+                    MathOp::Mod => a % b,
+                    MathOp::Min => a.min(b),
+                    MathOp::Max => a.max(b),
+                };
+                Number::from_f64(result)
+                    .ok_or_else(|| anyhow!("{} {} {} is not a number", a, self.name(), b))
+            }
+        }
+    }
+}
+
+impl CompareOp {
+    pub const ALL: [CompareOp; 3] = [CompareOp::Eq, CompareOp::Lt, CompareOp::Gt];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "Equal",
+            CompareOp::Lt => "Less Than",
+            CompareOp::Gt => "Greater Than",
+        }
+    }
+
+    pub fn apply(&self, a: &Number, b: &Number) -> anyhow::Result<bool> {
+        let ordering = match (Operand::new(a)?, Operand::new(b)?) {
+            (Operand::Int(a), Operand::Int(b)) => a.cmp(&b),
+            (a, b) => a
+                .as_f64()
+                .partial_cmp(&b.as_f64())
+                .ok_or_else(|| anyhow!("Cannot compare {} and {}", a.as_f64(), b.as_f64()))?,
+        };
+        Ok(match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Gt => ordering == Ordering::Greater,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(text: &str) -> Number {
+        Number::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn big_integers_keep_their_precision() {
+        let max = number("340282366920938463463374607431768211455");
+        assert_eq!(
+            MathOp::Add.apply(&max, &number("1")).unwrap().to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            MathOp::Div.apply(&max, &number("5")).unwrap().to_string(),
+            "68056473384187692692674921486353642291"
+        );
+        assert!(CompareOp::Gt
+            .apply(&max, &number("340282366920938463463374607431768211454"))
+            .unwrap());
+    }
+
+    #[test]
+    fn integer_division_truncates() {
+        assert_eq!(
+            MathOp::Div
+                .apply(&number("7"), &number("2"))
+                .unwrap()
+                .to_string(),
+            "3"
+        );
+        assert_eq!(
+            MathOp::Div
+                .apply(&number("-7"), &number("2"))
+                .unwrap()
+                .to_string(),
+            "-3"
+        );
+        assert_eq!(
+            MathOp::Mod
+                .apply(&number("-7"), &number("2"))
+                .unwrap()
+                .to_string(),
+            "-1"
+        );
+    }
+
+    #[test]
+    fn integer_division_by_zero_fails() {
+        for op in [MathOp::Div, MathOp::Mod] {
+            let err = op.apply(&number("5"), &number("0")).unwrap_err();
+            assert_eq!(err.to_string(), "Division by zero");
+        }
+    }
+
+    #[test]
+    fn float_division_by_zero_is_not_a_number() {
+        assert!(MathOp::Div.apply(&number("5.0"), &number("0")).is_err());
+        assert!(MathOp::Mod.apply(&number("5"), &number("0.0")).is_err());
+        assert_eq!(
+            MathOp::Div
+                .apply(&number("5"), &number("2.0"))
+                .unwrap()
+                .to_string(),
+            "2.5"
+        );
+    }
+}
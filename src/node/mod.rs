@@ -5,24 +5,48 @@
 // TODO: hover over node and see state
 // TODO: outline red if node errors
 
-// TODO: save or clone nodes
-
 // TODO: infinite loop countermeasure maybe using petgraph
 
+pub mod chain;
+pub mod cli;
 pub mod data;
+pub mod debugger;
 pub mod evaluator;
+pub mod fee;
+pub mod format;
+pub mod fragment;
+pub mod manifest;
+pub mod math;
+pub mod patch;
+pub mod path;
+pub mod schema;
 pub mod state;
+pub mod subgraph;
 pub mod template;
+pub mod transcript;
+pub mod wasm;
 
+use crate::node::chain::MockChain;
 use crate::node::data::{DataType, ValueType};
-use crate::node::evaluator::evaluate_node;
+use crate::node::debugger::Debugger;
+use crate::node::evaluator::{evaluate_node, peek_inputs, Environment};
+use crate::node::format::{placeholders, TEMPLATE_INPUT};
+use crate::node::fragment::Fragment;
+use crate::node::manifest::Manifest;
+use crate::node::schema::{ContractSchema, MsgKind};
 use crate::node::state::NodeState;
+use crate::node::subgraph::Subgraph;
 use crate::node::template::{add_param, remove_param, Template, TemplateIterator, IO};
+use crate::node::wasm::WasmCache;
 use eframe::egui::{Context, TextStyle};
 use eframe::{egui, App, Frame};
-use egui_node_graph::{Graph, GraphEditorState, NodeId, NodeResponse, UserResponseTrait};
+use egui_node_graph::{
+    Graph, GraphEditorState, NodeId, NodeResponse, NodeTemplateTrait, UserResponseTrait,
+};
 use std::collections::HashMap;
 use std::default::Default;
+use std::path::Path;
+use std::rc::Rc;
 
 type EditorState = GraphEditorState<NodeState, DataType, ValueType, Template, GraphState>;
 type NodeGraph = Graph<NodeState, DataType, ValueType>;
@@ -31,6 +55,8 @@ type NodeGraph = Graph<NodeState, DataType, ValueType>;
 pub struct OrchestratorNodeGraph {
     state: EditorState,
     user_state: GraphState,
+    /// Pastes since the last copy, each one lands further away so they don't stack
+    pastes: usize,
 }
 
 /// Global state for the graph side effects to add extra functionality
@@ -41,10 +67,53 @@ pub struct GraphState {
     pub editing_node: Option<NodeId>,
     pub json_name: String,
     pub new_type: DataType,
+    /// Port types being edited, see `Template::port_types`
+    pub port_types: Vec<DataType>,
+    pub condition_node: Option<NodeId>,
+    pub condition: String,
+    /// Path to a previous deployment manifest
+    pub deployment_path: String,
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub deployment: Option<Manifest>,
+    pub deployment_error: Option<String>,
+    /// Effectful nodes only simulate their txs
+    pub dry_run: bool,
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub wasm: WasmCache,
+    /// Dry runs are simulated against the mock until there's a client for a real chain
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub chain: MockChain,
+    /// Path to a contract's schema folder
+    pub schema_path: String,
+    pub schema_error: Option<String>,
+    /// Schemas loaded by contract interface nodes, their msgs are listed as templates
+    pub interfaces: Rc<Vec<ContractSchema>>,
+    /// Name for the selected nodes when collapsing them
+    pub subgraph_name: String,
+    /// Collapsed subgraphs, listed as templates
+    pub subgraphs: Rc<Vec<Subgraph>>,
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub debugger: Option<Debugger>,
+}
+
+impl GraphState {
+    pub fn environment(&self) -> Environment {
+        Environment {
+            deployment: self.deployment.as_ref(),
+            dry_run: self.dry_run,
+            wasm: &self.wasm,
+            chain: &self.chain,
+        }
+    }
 }
 
+/// Pasted nodes are moved a bit so they don't cover the copied ones, more for every paste
+const PASTE_OFFSET: egui::Vec2 = egui::vec2(20.0, 20.0);
+
 #[cfg(feature = "persistence")]
 const PERSISTENCE_KEY: &str = "egui_node_graph";
+#[cfg(feature = "persistence")]
+const SUBGRAPHS_KEY: &str = "subgraphs";
 
 #[cfg(feature = "persistence")]
 impl OrchestratorNodeGraph {
@@ -55,9 +124,17 @@ impl OrchestratorNodeGraph {
             .storage
             .and_then(|storage| eframe::get_value(storage, PERSISTENCE_KEY))
             .unwrap_or_default();
+        let subgraphs = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, SUBGRAPHS_KEY))
+            .unwrap_or_default();
         Self {
             state,
-            user_state: GraphState::default(),
+            user_state: GraphState {
+                subgraphs,
+                ..Default::default()
+            },
+            pastes: 0,
         }
     }
 }
@@ -67,13 +144,132 @@ impl App for OrchestratorNodeGraph {
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 egui::widgets::global_dark_light_mode_switch(ui);
+                ui.separator();
+
+                if let Some(debugger) = &mut self.user_state.debugger {
+                    if ui.button("⏭ Step").clicked() {
+                        debugger.step(&self.state.graph);
+                    }
+                    if ui.button("↷ Skip").clicked() {
+                        debugger.skip(&self.state.graph);
+                    }
+                    if ui.button("⏩ Continue").clicked() {
+                        debugger.resume(&self.state.graph);
+                    }
+                    if ui.button("💾 Export transcript").clicked() {
+                        let path = debugger.transcript.file_name();
+                        if let Err(err) = debugger.transcript.export(Path::new(&path)) {
+                            debugger.error = Some(format!("Could not export transcript: {}", err));
+                        }
+                    }
+                    if ui.button("⏹ Stop").clicked() {
+                        self.user_state.debugger = None;
+                    }
+                } else if let Some(node) = self.user_state.active_node {
+                    if ui.button("▶ Run").clicked() {
+                        self.user_state.debugger = Some(Debugger::run(
+                            &self.state.graph,
+                            node,
+                            self.user_state.deployment.clone(),
+                            self.user_state.dry_run,
+                        ));
+                    }
+                    if ui.button("🐞 Debug").clicked() {
+                        self.user_state.debugger = Some(Debugger::new(
+                            &self.state.graph,
+                            node,
+                            self.user_state.deployment.clone(),
+                            self.user_state.dry_run,
+                        ));
+                    }
+                }
+
+                ui.checkbox(&mut self.user_state.dry_run, "Dry run");
+
+                ui.separator();
+                ui.label("Previous deployment");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.user_state.deployment_path)
+                        .hint_text("deployment.json"),
+                );
+                if self.user_state.deployment.is_none() {
+                    if ui.button("Load").clicked() {
+                        match Manifest::read(Path::new(&self.user_state.deployment_path)) {
+                            Ok(manifest) => {
+                                self.user_state.deployment = Some(manifest);
+                                self.user_state.deployment_error = None;
+                            }
+                            Err(err) => self.user_state.deployment_error = Some(err.to_string()),
+                        }
+                    }
+                } else if ui.button("Unload").clicked() {
+                    self.user_state.deployment = None;
+                }
+                if let Some(err) = &self.user_state.deployment_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                if !self.state.selected_nodes.is_empty() {
+                    ui.separator();
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.user_state.subgraph_name)
+                            .hint_text("subgraph name"),
+                    );
+                    let name = self.user_state.subgraph_name.trim().to_string();
+                    if ui.button("Collapse").clicked() && !name.is_empty() {
+                        self.collapse_selection(name);
+                        self.user_state.subgraph_name.clear();
+                    }
+                }
             });
         });
 
+        // Text fields handle their own copy and paste
+        if !ctx.wants_keyboard_input() {
+            let events = ctx.input().events.clone();
+            for event in events {
+                match event {
+                    egui::Event::Copy => {
+                        self.pastes = 0;
+                        if let Some(fragment) = self.copy_selection() {
+                            if let Ok(text) = serde_json::to_string(&fragment) {
+                                ctx.output().copied_text = text;
+                            }
+                        }
+                    }
+                    // Anything that isn't a fragment is ignored
+                    egui::Event::Paste(text) => {
+                        if let Ok(fragment) = serde_json::from_str::<Fragment>(&text) {
+                            self.paste(&fragment);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let duplicate = {
+                let input = ctx.input();
+                input.modifiers.command && input.key_pressed(egui::Key::D)
+            };
+            if duplicate {
+                if let Some(fragment) = self.copy_selection() {
+                    self.pastes = 0;
+                    self.paste(&fragment);
+                }
+            }
+        }
+
         let graph_response = egui::CentralPanel::default()
             .show(ctx, |ui| {
-                self.state
-                    .draw_graph_editor(ui, TemplateIterator, &mut self.user_state)
+                self.state.draw_graph_editor(
+                    ui,
+                    // Only the Rc is cloned, the lists are shared with the iterator
+                    TemplateIterator {
+                        interfaces: self.user_state.interfaces.clone(),
+                        subgraphs: self.user_state.subgraphs.clone(),
+                    },
+                    &mut self.user_state,
+                )
             })
             .inner;
         for node_response in graph_response.node_responses {
@@ -81,6 +277,21 @@ impl App for OrchestratorNodeGraph {
             // some use for them. For example, by playing a sound when a new
             // connection is created
 
+            // A connected template changes its placeholders through the connection
+            if let NodeResponse::ConnectEventEnded { input, .. }
+            | NodeResponse::DisconnectEvent { input, .. } = node_response
+            {
+                // The input is already gone if its node was deleted
+                if let Some(param) = self.state.graph.inputs.get(input) {
+                    let id = param.node;
+                    if self.state.graph[id].user_data.template == Template::FormatString
+                        && self.state.graph[id].get_input(TEMPLATE_INPUT).ok() == Some(input)
+                    {
+                        self.sync_placeholders(id);
+                    }
+                }
+            }
+
             if let NodeResponse::User(user_event) = node_response {
                 match user_event {
                     Response::SetActiveNode(node) => self.user_state.active_node = Some(node),
@@ -88,21 +299,108 @@ impl App for OrchestratorNodeGraph {
                     Response::SetEditingNode(node) => {
                         self.user_state.editing_node = Some(node);
                         self.user_state.json_name.clear();
-                        self.user_state.new_type = match self.state.graph[node].user_data.template {
-                            Template::ConstructArray(data) => data,
-                            Template::DeconstructArray(data) => data,
+                        self.user_state.new_type = match &self.state.graph[node].user_data.template
+                        {
+                            Template::ConstructArray(data) => data.clone(),
+                            Template::DeconstructArray(data) => data.clone(),
                             _ => DataType::Bool,
                         };
+                        self.user_state.port_types =
+                            self.state.graph[node].user_data.template.port_types();
                     }
                     Response::ClearEditingNode => {
                         self.user_state.editing_node = None;
+                        self.user_state.schema_error = None;
+                    }
+                    Response::LoadSchema(id) => {
+                        match ContractSchema::load(Path::new(&self.user_state.schema_path)) {
+                            Ok(schema) => {
+                                let node = &mut self.state.graph[id].user_data;
+                                // Instantiate only has the one msg
+                                node.variant = match node.template {
+                                    Template::Instantiate => schema
+                                        .variants
+                                        .iter()
+                                        .find(|variant| variant.kind == MsgKind::Instantiate)
+                                        .cloned(),
+                                    _ => None,
+                                };
+                                if node.template == Template::ContractInterface {
+                                    let interfaces = Rc::make_mut(&mut self.user_state.interfaces);
+                                    interfaces.retain(|interface| interface.name != schema.name);
+                                    interfaces.push(schema.clone());
+                                }
+                                node.schema = Some(schema);
+                                self.user_state.schema_error = None;
+
+                                let template = node.template.clone();
+                                self.rebuild_node(id, template);
+                            }
+                            Err(err) => self.user_state.schema_error = Some(err.to_string()),
+                        }
+                    }
+                    Response::UnloadSchema(id) => {
+                        let node = &mut self.state.graph[id].user_data;
+                        if let (Template::ContractInterface, Some(schema)) =
+                            (&node.template, &node.schema)
+                        {
+                            Rc::make_mut(&mut self.user_state.interfaces)
+                                .retain(|interface| interface.name != schema.name);
+                        }
+                        node.schema = None;
+                        node.variant = None;
+
+                        let template = node.template.clone();
+                        self.rebuild_node(id, template);
+                    }
+                    Response::SelectVariant(id, i) => {
+                        let node = &mut self.state.graph[id].user_data;
+                        let variant = node
+                            .schema
+                            .as_ref()
+                            .and_then(|schema| i.and_then(|i| schema.variants.get(i)).cloned());
+                        // Avoid dropping connections if nothing changed
+                        if variant != node.variant {
+                            node.variant = variant;
+
+                            let template = node.template.clone();
+                            self.rebuild_node(id, template);
+                        }
+                    }
+                    Response::ToggleBreakpoint(node) => {
+                        let node = &mut self.state.graph[node].user_data;
+                        node.breakpoint = !node.breakpoint;
+                    }
+                    Response::EditCondition(node) => {
+                        self.user_state.condition_node = Some(node);
+                        self.user_state.condition =
+                            self.state.graph[node].user_data.condition.clone();
+                    }
+                    Response::SetCondition(node) => {
+                        self.state.graph[node].user_data.condition =
+                            self.user_state.condition.trim().to_string();
+                        self.user_state.condition_node = None;
+                        self.user_state.condition.clear();
+                    }
+                    Response::WriteDeployment(id) => {
+                        self.user_state.deployment_error = self
+                            .write_deployment(id)
+                            .err()
+                            .map(|err| format!("Could not write the deployment: {}", err));
+                    }
+                    Response::ToggleStrict(node) => {
+                        let node = &mut self.state.graph[node].user_data;
+                        node.strict = !node.strict;
                     }
                     Response::AddParam(id) => {
-                        let types = match self.state.graph[id].user_data.template {
+                        let types = match &self.state.graph[id].user_data.template {
                             Template::ConstructJson => Some((IO::Input, None)),
+                            Template::DeploymentOutput => Some((IO::Input, None)),
                             Template::DeconstructJson => Some((IO::Output, None)),
-                            Template::ConstructArray(data) => Some((IO::Input, Some(data))),
-                            Template::DeconstructArray(data) => Some((IO::Output, Some(data))),
+                            Template::ConstructArray(data) => Some((IO::Input, Some(data.clone()))),
+                            Template::DeconstructArray(data) => {
+                                Some((IO::Output, Some(data.clone())))
+                            }
                             _ => None,
                         };
 
@@ -124,7 +422,7 @@ impl App for OrchestratorNodeGraph {
                             } else {
                                 add_param(
                                     id,
-                                    self.user_state.new_type,
+                                    self.user_state.new_type.clone(),
                                     &self.user_state.json_name,
                                     io,
                                     &mut self.state.graph,
@@ -137,6 +435,7 @@ impl App for OrchestratorNodeGraph {
                     Response::RemoveParam(id) => {
                         let types = match self.state.graph[id].user_data.template {
                             Template::ConstructJson => Some((IO::Input, false)),
+                            Template::DeploymentOutput => Some((IO::Input, false)),
                             Template::DeconstructJson => Some((IO::Output, false)),
                             Template::ConstructArray(_) => Some((IO::Input, true)),
                             Template::DeconstructArray(_) => Some((IO::Output, true)),
@@ -162,17 +461,31 @@ impl App for OrchestratorNodeGraph {
                             }
                         }
                     }
+                    Response::UpdatePlaceholders(id) => self.sync_placeholders(id),
+                    Response::UpdatePortTypes(id) => {
+                        let current = &self.state.graph[id].user_data.template;
+                        // Avoid dropping connections if nothing changed
+                        let changed = current
+                            .port_types()
+                            .iter()
+                            .zip(self.user_state.port_types.iter())
+                            .any(|(current, new)| !current.is(new));
+                        if changed {
+                            let template = current.with_port_types(&self.user_state.port_types);
+                            self.rebuild_node(id, template);
+                        }
+                    }
                     Response::UpdateArrayType(id) => {
                         // We need to rewrite all of the params
-                        let types = match self.state.graph[id].user_data.template {
-                            Template::ConstructArray(data) => Some((IO::Input, data)),
-                            Template::DeconstructArray(data) => Some((IO::Output, data)),
+                        let types = match &self.state.graph[id].user_data.template {
+                            Template::ConstructArray(data) => Some((IO::Input, data.clone())),
+                            Template::DeconstructArray(data) => Some((IO::Output, data.clone())),
                             _ => None,
                         };
 
                         if let Some((io, data)) = types {
                             // Avoid updating if we arent changing the data type
-                            if data != self.user_state.new_type {
+                            if !data.is(&self.user_state.new_type) {
                                 let total = match io {
                                     IO::Input => self.state.graph[id].inputs.len(),
                                     IO::Output => self.state.graph[id].outputs.len(),
@@ -187,19 +500,35 @@ impl App for OrchestratorNodeGraph {
                                 for i in 0..total {
                                     add_param(
                                         id,
-                                        self.user_state.new_type,
+                                        self.user_state.new_type.clone(),
                                         &i.to_string(),
                                         io,
                                         &mut self.state.graph,
                                     );
                                 }
 
+                                // The array itself also changes type
+                                let (name, array_io) = match io {
+                                    IO::Input => ("out", IO::Output),
+                                    IO::Output => ("array", IO::Input),
+                                };
+                                remove_param(id, name, array_io, &mut self.state.graph);
+                                add_param(
+                                    id,
+                                    DataType::Array(Box::new(self.user_state.new_type.clone())),
+                                    name,
+                                    array_io,
+                                    &mut self.state.graph,
+                                );
+
                                 self.state.graph[id].user_data.template =
                                     match self.state.graph[id].user_data.template {
-                                        Template::ConstructArray(_) => {
-                                            Template::ConstructArray(self.user_state.new_type)
-                                        }
-                                        _ => Template::DeconstructArray(self.user_state.new_type),
+                                        Template::ConstructArray(_) => Template::ConstructArray(
+                                            self.user_state.new_type.clone(),
+                                        ),
+                                        _ => Template::DeconstructArray(
+                                            self.user_state.new_type.clone(),
+                                        ),
                                     }
                             }
                         }
@@ -208,9 +537,34 @@ impl App for OrchestratorNodeGraph {
             }
         }
 
-        if let Some(node) = self.user_state.active_node {
+        if let Some(debugger) = &self.user_state.debugger {
+            let (step, total) = debugger.progress();
+            let text = if let Some(err) = &debugger.error {
+                format!("Execution error: {}", err)
+            } else if let Some(value) = &debugger.result {
+                if value.len() == 1 {
+                    format!("The result is: {:?}", value[0])
+                } else {
+                    format!("The result is: {:?}", value)
+                }
+            } else {
+                format!("Paused at step {} of {}", step + 1, total)
+            };
+            ctx.debug_painter().text(
+                egui::pos2(10.0, 35.0),
+                egui::Align2::LEFT_TOP,
+                text,
+                TextStyle::Button.resolve(&ctx.style()),
+                egui::Color32::WHITE,
+            );
+        } else if let Some(node) = self.user_state.active_node {
             if self.state.graph.nodes.contains_key(node) {
-                let text = match evaluate_node(&self.state.graph, node, &mut HashMap::new()) {
+                let text = match evaluate_node(
+                    &self.state.graph,
+                    node,
+                    &mut HashMap::new(),
+                    self.user_state.environment(),
+                ) {
                     Ok(value) => {
                         if value.len() == 1 {
                             format!("The result is: {:?}", value[0])
@@ -236,6 +590,199 @@ impl App for OrchestratorNodeGraph {
     #[cfg(feature = "persistence")]
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, PERSISTENCE_KEY, &self.state);
+        eframe::set_value(storage, SUBGRAPHS_KEY, &self.user_state.subgraphs);
+    }
+}
+
+impl OrchestratorNodeGraph {
+    /// Adds a node like the node finder does
+    fn add_node(&mut self, template: Template, position: egui::Pos2) -> NodeId {
+        let user_data = template.user_data(&mut self.user_state);
+        let label = template.node_graph_label(&mut self.user_state);
+        let id = self.state.graph.add_node(label, user_data, |graph, id| {
+            template.build_node(graph, &mut self.user_state, id)
+        });
+        self.state.node_positions.insert(id, position);
+        self.state.node_order.push(id);
+        id
+    }
+
+    fn remove_node(&mut self, id: NodeId) {
+        self.state.graph.remove_node(id);
+        self.state.node_positions.remove(id);
+        self.state.node_order.retain(|node| *node != id);
+        self.state.selected_nodes.retain(|node| *node != id);
+    }
+
+    /// Copies the selected nodes with their values and the connections between them
+    fn copy_selection(&self) -> Option<Fragment> {
+        if self.state.selected_nodes.is_empty() {
+            return None;
+        }
+        let positions = &self.state.node_positions;
+        Some(Fragment::from_nodes(
+            &self.state.graph,
+            &self.state.selected_nodes,
+            |id| positions.get(id).copied().unwrap_or_default(),
+        ))
+    }
+
+    /// Adds the fragment next to where it was copied from and selects it
+    fn paste(&mut self, fragment: &Fragment) {
+        let ids = fragment.insert(&mut self.state.graph, &mut self.user_state);
+        self.pastes += 1;
+        let origin =
+            egui::pos2(fragment.origin[0], fragment.origin[1]) + PASTE_OFFSET * self.pastes as f32;
+        for (id, node) in ids.iter().zip(fragment.nodes.iter()) {
+            let offset = egui::vec2(node.offset[0], node.offset[1]);
+            self.state.node_positions.insert(*id, origin + offset);
+            self.state.node_order.push(*id);
+        }
+        for id in ids.iter() {
+            if self.state.graph[*id].user_data.template == Template::FormatString {
+                self.sync_placeholders(*id);
+            }
+        }
+        self.state.selected_nodes = ids;
+    }
+
+    /// Replaces the selected nodes with a subgraph node and keeps their outside connections
+    fn collapse_selection(&mut self, name: String) {
+        let graph = &self.state.graph;
+        let nodes: Vec<NodeId> = self
+            .state
+            .selected_nodes
+            .iter()
+            .copied()
+            .filter(|id| graph.nodes.contains_key(*id))
+            .collect();
+        if nodes.is_empty() {
+            return;
+        }
+        let positions = &self.state.node_positions;
+        let position = |id| positions.get(id).copied().unwrap_or_default();
+        let origin = position(nodes[0]);
+        let (subgraph, boundary) = Subgraph::collapse(name, &self.state.graph, &nodes, position);
+
+        for id in nodes {
+            self.remove_node(id);
+        }
+
+        let subgraphs = Rc::make_mut(&mut self.user_state.subgraphs);
+        subgraphs.retain(|existing| existing.name != subgraph.name);
+        subgraphs.push(subgraph.clone());
+
+        let id = self.add_node(Template::Subgraph(Box::new(subgraph)), origin);
+        for (output, name) in boundary.inputs {
+            if let Ok(input) = self.state.graph[id].get_input(&name) {
+                self.state.graph.add_connection(output, input);
+            }
+        }
+        for (name, input) in boundary.outputs {
+            if let Ok(output) = self.state.graph[id].get_output(&name) {
+                self.state.graph.add_connection(output, input);
+            }
+        }
+    }
+
+    /// Evaluates a deployment output node and writes its manifest to its file input
+    fn write_deployment(&self, id: NodeId) -> anyhow::Result<()> {
+        if self.user_state.dry_run {
+            anyhow::bail!("A dry run has no addresses or code ids to record, turn it off first");
+        }
+        let graph = &self.state.graph;
+        let mut cache = HashMap::new();
+        evaluate_node(graph, id, &mut cache, self.user_state.environment())?;
+
+        let manifest = cache
+            .get(&graph[id].get_output("manifest")?)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("The manifest was not evaluated"))?;
+        let file: String = peek_inputs(graph, id, &cache)
+            .into_iter()
+            .find_map(|(name, value)| if name == "file" { value } else { None })
+            .ok_or_else(|| anyhow::anyhow!("The file was not evaluated"))?
+            .try_into()?;
+
+        Manifest::from_json(manifest.try_into()?)?.write(Path::new(&file))
+    }
+
+    /// Syncs a string template's inputs with its placeholders, a connected template is evaluated
+    fn sync_placeholders(&mut self, id: NodeId) {
+        let graph = &self.state.graph;
+        let input = match graph[id].get_input(TEMPLATE_INPUT) {
+            Ok(input) => input,
+            Err(_) => return,
+        };
+        let value = match graph.connection(input) {
+            Some(output) => {
+                let mut cache = HashMap::new();
+                evaluate_node(
+                    graph,
+                    graph[output].node,
+                    &mut cache,
+                    self.user_state.environment(),
+                )
+                .ok()
+                .and_then(|_| cache.remove(&output))
+            }
+            None => Some(graph[input].value.clone().resolved()),
+        };
+        // Keep the current inputs until the template can be read
+        let template: String = match value.and_then(|value| value.try_into().ok()) {
+            Some(template) => template,
+            None => return,
+        };
+
+        // Keep them as well while a placeholder is invalid, evaluating shows the error
+        let names = match placeholders(&template) {
+            Ok(names) => names,
+            Err(_) => return,
+        };
+        // The reserved name is rejected when evaluating instead of shadowing the template
+        let names: Vec<_> = names
+            .into_iter()
+            .filter(|name| name != TEMPLATE_INPUT)
+            .collect();
+
+        let existing: Vec<_> = graph[id]
+            .inputs
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| name != TEMPLATE_INPUT)
+            .collect();
+
+        // Only touch changed placeholders to keep the other connections
+        for name in existing.iter().filter(|name| !names.contains(name)) {
+            remove_param(id, name, IO::Input, &mut self.state.graph);
+        }
+        for name in names.iter().filter(|name| !existing.contains(name)) {
+            add_param(id, DataType::String, name, IO::Input, &mut self.state.graph);
+        }
+    }
+
+    /// Replaces the node template and rebuilds all of its params, connections are dropped
+    fn rebuild_node(&mut self, id: NodeId, template: Template) {
+        let inputs: Vec<_> = self.state.graph[id]
+            .inputs
+            .iter()
+            .map(|(_, input)| *input)
+            .collect();
+        for input in inputs {
+            self.state.graph.remove_input_param(input);
+        }
+
+        let outputs: Vec<_> = self.state.graph[id]
+            .outputs
+            .iter()
+            .map(|(_, output)| *output)
+            .collect();
+        for output in outputs {
+            self.state.graph.remove_output_param(output);
+        }
+
+        template.build_node(&mut self.state.graph, &mut self.user_state, id);
+        self.state.graph[id].user_data.template = template;
     }
 }
 
@@ -256,6 +803,29 @@ pub enum Response {
     RemoveParam(NodeId),
 
     UpdateArrayType(NodeId),
+    /// Syncs a string template's inputs with its placeholders
+    UpdatePlaceholders(NodeId),
+    /// Rebuilds the node with the edited port types
+    UpdatePortTypes(NodeId),
+
+    /// Pause the debugger on this node
+    ToggleBreakpoint(NodeId),
+    /// Determines which node's breakpoint condition to edit
+    EditCondition(NodeId),
+    /// Saves the edited breakpoint condition
+    SetCondition(NodeId),
+
+    /// Writes the deployment output node's manifest to its file
+    WriteDeployment(NodeId),
+
+    /// Toggles strict mode on nodes reading typed values out of json
+    ToggleStrict(NodeId),
+
+    /// Loads the contract schema in `GraphState::schema_path` into the node
+    LoadSchema(NodeId),
+    UnloadSchema(NodeId),
+    /// Picks the schema variant the msg ports are generated from, `None` uses raw json
+    SelectVariant(NodeId, Option<usize>),
 }
 
 impl UserResponseTrait for Response {}
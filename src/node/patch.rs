@@ -0,0 +1,212 @@
+use crate::node::path::{index, pointer_tokens};
+use anyhow::anyhow;
+use serde_json::Value;
+
+/// Recursively merges the overlay's objects into the base, anything else in the overlay replaces
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Applies an RFC 6902 JSON Patch, nothing is changed if any operation fails
+pub fn apply(json: &Value, patch: &Value) -> anyhow::Result<Value> {
+    let operations = patch
+        .as_array()
+        .ok_or_else(|| anyhow!("Patch should be an array of operations"))?;
+
+    let mut json = json.clone();
+    for (i, operation) in operations.iter().enumerate() {
+        apply_operation(&mut json, operation).map_err(|err| anyhow!("Operation {}: {}", i, err))?;
+    }
+    Ok(json)
+}
+
+fn apply_operation(json: &mut Value, operation: &Value) -> anyhow::Result<()> {
+    let field = |name: &str| {
+        operation[name]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing {}", name))
+    };
+    let value = || {
+        operation
+            .get("value")
+            .cloned()
+            .ok_or_else(|| anyhow!("Missing value"))
+    };
+
+    let path = field("path")?;
+    match field("op")? {
+        "add" => add(json, path, value()?),
+        "remove" => remove(json, path).map(|_| ()),
+        "replace" => {
+            let target = json
+                .pointer_mut(path)
+                .ok_or_else(|| anyhow!("Nothing to replace at {}", path))?;
+            *target = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = field("from")?;
+            if path.starts_with(&format!("{}/", from)) {
+                anyhow::bail!("Cannot move {} into itself", from);
+            }
+            let moved = remove(json, from)?;
+            add(json, path, moved)
+        }
+        "copy" => {
+            let from = field("from")?;
+            let copied = json
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| anyhow!("Nothing to copy at {}", from))?;
+            add(json, path, copied)
+        }
+        "test" => {
+            let expected = value()?;
+            match json.pointer(path) {
+                Some(actual) if *actual == expected => Ok(()),
+                Some(actual) => anyhow::bail!("{} is {} not {}", path, actual, expected),
+                None => anyhow::bail!("Nothing to test at {}", path),
+            }
+        }
+        op => anyhow::bail!("Unknown op {}", op),
+    }
+}
+
+/// Splits the pointer into its parent and last token
+fn parent<'a>(json: &'a mut Value, path: &str) -> anyhow::Result<(&'a mut Value, String)> {
+    let mut tokens = pointer_tokens(path)?;
+    let last = tokens
+        .pop()
+        .ok_or_else(|| anyhow!("Cannot change the whole document"))?;
+
+    let mut parent = json;
+    for token in tokens {
+        parent = match parent {
+            Value::Object(object) => object.get_mut(&token),
+            Value::Array(array) => {
+                let i = index(&token, array.len(), false)?;
+                array.get_mut(i)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("Nothing at {}", path))?;
+    }
+    Ok((parent, last))
+}
+
+fn add(json: &mut Value, path: &str, value: Value) -> anyhow::Result<()> {
+    if path.is_empty() {
+        *json = value;
+        return Ok(());
+    }
+
+    match parent(json, path)? {
+        (Value::Object(object), key) => {
+            object.insert(key, value);
+        }
+        (Value::Array(array), token) => {
+            let i = index(&token, array.len(), true)?;
+            array.insert(i, value);
+        }
+        _ => anyhow::bail!("Cannot add to {}", path),
+    }
+    Ok(())
+}
+
+fn remove(json: &mut Value, path: &str) -> anyhow::Result<Value> {
+    match parent(json, path)? {
+        (Value::Object(object), key) => object
+            .remove(&key)
+            .ok_or_else(|| anyhow!("Nothing to remove at {}", path)),
+        (Value::Array(array), token) => {
+            let i = index(&token, array.len(), false)?;
+            Ok(array.remove(i))
+        }
+        _ => anyhow::bail!("Nothing to remove at {}", path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_recurses_into_objects() {
+        let mut base = json!({ "a": { "b": 1, "c": 2 }, "d": [1] });
+        merge(&mut base, json!({ "a": { "c": 3 }, "d": [2] }));
+        assert_eq!(base, json!({ "a": { "b": 1, "c": 3 }, "d": [2] }));
+    }
+
+    #[test]
+    fn add_appends_with_dash() {
+        let json = json!({ "list": [1, 2] });
+        let patch = json!([
+            { "op": "add", "path": "/list/-", "value": 3 },
+            { "op": "add", "path": "/list/0", "value": 0 },
+        ]);
+        assert_eq!(
+            apply(&json, &patch).unwrap(),
+            json!({ "list": [0, 1, 2, 3] })
+        );
+
+        let patch = json!([{ "op": "remove", "path": "/list/-" }]);
+        assert!(apply(&json, &patch).is_err());
+    }
+
+    #[test]
+    fn move_and_copy() {
+        let json = json!({ "a": { "b": 1 }, "c": [] });
+        let patch = json!([
+            { "op": "copy", "from": "/a/b", "path": "/c/-" },
+            { "op": "move", "from": "/a", "path": "/e" },
+        ]);
+        assert_eq!(
+            apply(&json, &patch).unwrap(),
+            json!({ "c": [1], "e": { "b": 1 } })
+        );
+
+        let patch = json!([{ "op": "move", "from": "/a", "path": "/a/b" }]);
+        assert!(apply(&json, &patch).is_err());
+        let patch = json!([{ "op": "copy", "from": "/x", "path": "/y" }]);
+        assert!(apply(&json, &patch).is_err());
+    }
+
+    #[test]
+    fn failed_test_changes_nothing() {
+        let json = json!({ "a": 1 });
+        let passing = json!([
+            { "op": "test", "path": "/a", "value": 1 },
+            { "op": "replace", "path": "/a", "value": 2 },
+        ]);
+        assert_eq!(apply(&json, &passing).unwrap(), json!({ "a": 2 }));
+
+        let failing = json!([
+            { "op": "replace", "path": "/a", "value": 2 },
+            { "op": "test", "path": "/a", "value": 1 },
+        ]);
+        let err = apply(&json, &failing).unwrap_err().to_string();
+        assert!(err.starts_with("Operation 1"), "{}", err);
+        assert_eq!(json, json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn invalid_patches_are_rejected() {
+        let json = json!({ "a": 1 });
+        assert!(apply(&json, &json!({ "op": "add" })).is_err());
+        assert!(apply(&json, &json!([{ "op": "frobnicate", "path": "/a" }])).is_err());
+        assert!(apply(&json, &json!([{ "op": "add", "path": "/a" }])).is_err());
+        assert!(apply(
+            &json,
+            &json!([{ "op": "replace", "path": "/b", "value": 1 }])
+        )
+        .is_err());
+    }
+}
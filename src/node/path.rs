@@ -0,0 +1,310 @@
+use anyhow::anyhow;
+use serde_json::Value;
+
+/// Selects from json using either a JSON Pointer (`/a/0/b`) or a JSONPath (`$.a[*].b`).
+/// Paths with wildcards or recursive descent always return an array of their matches.
+pub fn select(json: &Value, path: &str) -> anyhow::Result<Value> {
+    let path = path.trim();
+
+    if !path.starts_with('$') {
+        return json
+            .pointer(path)
+            .cloned()
+            .ok_or_else(|| anyhow!("Nothing found at {}", path));
+    }
+
+    let segments = parse(path)?;
+    let definite = segments.iter().all(|segment| match segment {
+        Segment::Key(_) | Segment::Index(_) => true,
+        Segment::Wildcard | Segment::Descend(_) => false,
+    });
+
+    let mut matches = vec![json];
+    for segment in segments.iter() {
+        matches = matches
+            .into_iter()
+            .flat_map(|value| segment.apply(value))
+            .collect();
+    }
+
+    if definite {
+        matches
+            .first()
+            .map(|value| (*value).clone())
+            .ok_or_else(|| anyhow!("Nothing found at {}", path))
+    } else {
+        Ok(Value::Array(matches.into_iter().cloned().collect()))
+    }
+}
+
+enum Segment {
+    Key(String),
+    /// Negative indexes count from the end
+    Index(i64),
+    Wildcard,
+    /// `..key`, or `..*` when empty
+    Descend(Option<String>),
+}
+
+impl Segment {
+    fn apply<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match self {
+            Segment::Key(key) => value.get(key).into_iter().collect(),
+            Segment::Index(i) => {
+                let array = match value.as_array() {
+                    Some(array) => array,
+                    None => return vec![],
+                };
+                let i = if *i < 0 { array.len() as i64 + i } else { *i };
+                if i < 0 {
+                    return vec![];
+                }
+                array.get(i as usize).into_iter().collect()
+            }
+            Segment::Wildcard => children(value),
+            Segment::Descend(key) => {
+                let mut descendants = vec![];
+                descend(value, &mut descendants);
+                match key {
+                    Some(key) => descendants
+                        .into_iter()
+                        .filter_map(|value| value.get(key))
+                        .collect(),
+                    None => descendants.into_iter().flat_map(children).collect(),
+                }
+            }
+        }
+    }
+}
+
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(array) => array.iter().collect(),
+        Value::Object(object) => object.values().collect(),
+        _ => vec![],
+    }
+}
+
+/// The value and everything nested in it
+fn descend<'a>(value: &'a Value, descendants: &mut Vec<&'a Value>) {
+    descendants.push(value);
+    for child in children(value) {
+        descend(child, descendants);
+    }
+}
+
+fn parse(path: &str) -> anyhow::Result<Vec<Segment>> {
+    let chars: Vec<char> = path.chars().collect();
+    let mut segments = vec![];
+    // Skip the root
+    let mut i = 1;
+
+    let name = |i: &mut usize| {
+        let start = *i;
+        while *i < chars.len() && chars[*i] != '.' && chars[*i] != '[' {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect::<String>()
+    };
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' if chars.get(i + 1) == Some(&'.') => {
+                i += 2;
+                let key = name(&mut i);
+                segments.push(Segment::Descend(match key.as_str() {
+                    "*" | "" => None,
+                    _ => Some(key),
+                }));
+            }
+            '.' => {
+                i += 1;
+                let key = name(&mut i);
+                segments.push(match key.as_str() {
+                    "" => anyhow::bail!("Empty key in {}", path),
+                    "*" => Segment::Wildcard,
+                    _ => Segment::Key(key),
+                });
+            }
+            '[' => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .ok_or_else(|| anyhow!("Unclosed [ in {}", path))?;
+                let inner: String = chars[i + 1..i + end].iter().collect();
+                let inner = inner.trim();
+                i += end + 1;
+
+                segments.push(if inner == "*" {
+                    Segment::Wildcard
+                } else if let Some(key) = inner
+                    .strip_prefix('\'')
+                    .and_then(|key| key.strip_suffix('\''))
+                    .or_else(|| {
+                        inner
+                            .strip_prefix('"')
+                            .and_then(|key| key.strip_suffix('"'))
+                    })
+                {
+                    Segment::Key(key.to_string())
+                } else {
+                    Segment::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| anyhow!("Invalid index {} in {}", inner, path))?,
+                    )
+                });
+            }
+            c => anyhow::bail!("Unexpected {} in {}", c, path),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Sets the value at a JSON Pointer or wildcard free JSONPath, missing objects are created
+pub fn set(json: &mut Value, path: &str, value: Value) -> anyhow::Result<()> {
+    let path = path.trim();
+
+    let segments = if path.starts_with('$') {
+        parse(path)?
+    } else {
+        pointer_tokens(path)?
+            .into_iter()
+            .map(Segment::Key)
+            .collect()
+    };
+
+    let mut current = json;
+    for segment in segments.iter() {
+        if current.is_null() {
+            *current = Value::Object(Default::default());
+        }
+        current = match (segment, current) {
+            (Segment::Key(key), Value::Object(object)) => {
+                object.entry(key.clone()).or_insert(Value::Null)
+            }
+            (Segment::Key(key), Value::Array(array)) => {
+                let i = index(key, array.len(), true)?;
+                if i == array.len() {
+                    array.push(Value::Null);
+                }
+                &mut array[i]
+            }
+            (Segment::Index(i), Value::Array(array)) => {
+                let len = array.len() as i64;
+                let i = if *i < 0 { len + i } else { *i };
+                if i < 0 || i >= len {
+                    anyhow::bail!("Index {} is out of bounds in {}", i, path);
+                }
+                &mut array[i as usize]
+            }
+            (Segment::Wildcard | Segment::Descend(_), _) => {
+                anyhow::bail!("Cannot set a wildcard path {}", path)
+            }
+            (_, value) => anyhow::bail!("Cannot index into {} in {}", value, path),
+        };
+    }
+
+    *current = value;
+    Ok(())
+}
+
+/// Unescaped JSON Pointer tokens
+pub fn pointer_tokens(pointer: &str) -> anyhow::Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    if !pointer.starts_with('/') {
+        anyhow::bail!("Pointer {} should start with /", pointer);
+    }
+    Ok(pointer[1..]
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Array index of a pointer token, `-` is the end of the array when appending
+pub fn index(token: &str, len: usize, append: bool) -> anyhow::Result<usize> {
+    let i = match token {
+        "-" if append => len,
+        _ => token
+            .parse()
+            .map_err(|_| anyhow!("Invalid array index {}", token))?,
+    };
+    if i > len || (i == len && !append) {
+        anyhow::bail!("Index {} is out of bounds", i);
+    }
+    Ok(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> Value {
+        json!({
+            "a": [{ "b": 1 }, { "b": 2 }, { "c": 3 }],
+            "d": { "b": 4 }
+        })
+    }
+
+    #[test]
+    fn pointers_and_definite_paths() {
+        assert_eq!(select(&doc(), "/a/1/b").unwrap(), json!(2));
+        assert_eq!(select(&doc(), "$.a[1].b").unwrap(), json!(2));
+        assert_eq!(select(&doc(), "$['d'][\"b\"]").unwrap(), json!(4));
+        assert!(select(&doc(), "/a/5").is_err());
+        assert!(select(&doc(), "$.a[5]").is_err());
+    }
+
+    #[test]
+    fn wildcards_return_all_matches() {
+        assert_eq!(select(&doc(), "$.a[*].b").unwrap(), json!([1, 2]));
+        assert_eq!(select(&doc(), "$.d.*").unwrap(), json!([4]));
+        assert_eq!(select(&doc(), "$.a[*].e").unwrap(), json!([]));
+    }
+
+    #[test]
+    fn descent_finds_nested_keys() {
+        assert_eq!(select(&doc(), "$..b").unwrap(), json!([1, 2, 4]));
+        assert_eq!(
+            select(&json!({ "x": { "y": [5] } }), "$..*").unwrap(),
+            json!([{ "y": [5] }, [5], 5])
+        );
+    }
+
+    #[test]
+    fn negative_indexes_count_from_the_end() {
+        assert_eq!(select(&doc(), "$.a[-1].c").unwrap(), json!(3));
+        assert_eq!(select(&doc(), "$.a[-3].b").unwrap(), json!(1));
+        assert!(select(&doc(), "$.a[-4]").is_err());
+
+        let mut json = doc();
+        set(&mut json, "$.a[-1].c", json!(6)).unwrap();
+        assert_eq!(json["a"][2]["c"], json!(6));
+        assert!(set(&mut json, "$.a[-4]", json!(0)).is_err());
+    }
+
+    #[test]
+    fn invalid_paths_are_rejected() {
+        assert!(select(&doc(), "$.").is_err());
+        assert!(select(&doc(), "$.a[0").is_err());
+        assert!(select(&doc(), "$.a[x]").is_err());
+        assert!(select(&doc(), "a").is_err());
+    }
+
+    #[test]
+    fn set_creates_missing_objects() {
+        let mut json = Value::Null;
+        set(&mut json, "$.a.b", json!(1)).unwrap();
+        set(&mut json, "/a/c~1d", json!(2)).unwrap();
+        assert_eq!(json, json!({ "a": { "b": 1, "c/d": 2 } }));
+
+        let mut json = json!({ "list": [1] });
+        set(&mut json, "/list/-", json!(2)).unwrap();
+        assert_eq!(json, json!({ "list": [1, 2] }));
+        assert!(set(&mut json, "$.list[*]", json!(0)).is_err());
+    }
+}
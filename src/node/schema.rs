@@ -0,0 +1,429 @@
+use crate::node::data::DataType;
+use anyhow::anyhow;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum MsgKind {
+    Instantiate,
+    Execute,
+    Query,
+}
+
+/// A message the contract accepts, the instantiate msg is a single variant without a wrapper
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Variant {
+    pub kind: MsgKind,
+    pub name: String,
+    pub fields: Vec<(String, DataType)>,
+    /// Unit variants are sent as a plain string
+    pub unit: bool,
+    /// Typed fields of the query response, when the schema describes it
+    pub response: Vec<(String, DataType)>,
+}
+
+impl Variant {
+    pub fn label(&self) -> String {
+        match self.kind {
+            MsgKind::Instantiate => "instantiate".to_string(),
+            MsgKind::Execute => format!("execute {}", self.name),
+            MsgKind::Query => format!("query {}", self.name),
+        }
+    }
+
+    /// Wraps the fields the way the contract expects them
+    pub fn msg(&self, fields: Map<String, Value>) -> Value {
+        match self.kind {
+            MsgKind::Instantiate => Value::Object(fields),
+            _ if self.unit => Value::String(self.name.clone()),
+            _ => {
+                let mut msg = Map::new();
+                msg.insert(self.name.clone(), Value::Object(fields));
+                Value::Object(msg)
+            }
+        }
+    }
+}
+
+/// Messages described by the json schemas cosmwasm contracts ship in their `schema` folder.
+/// Supports both the combined `<contract>.json` file and the older one file per message layout.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ContractSchema {
+    pub name: String,
+    pub instantiate: Option<Value>,
+    pub execute: Option<Value>,
+    pub query: Option<Value>,
+    /// Query responses by query name
+    pub responses: Map<String, Value>,
+    pub variants: Vec<Variant>,
+}
+
+impl ContractSchema {
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|err| anyhow!("Could not read {}: {}", dir.display(), err))?;
+
+        let mut schema = Self {
+            name: dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path)?;
+            let json: Value = serde_json::from_str(&content)
+                .map_err(|err| anyhow!("Invalid json in {}: {}", path.display(), err))?;
+
+            // Combined schema generated by cosmwasm-schema
+            if json.get("contract_name").is_some() {
+                if let Some(name) = json["contract_name"].as_str() {
+                    schema.name = name.to_string();
+                }
+                schema.instantiate = json.get("instantiate").cloned();
+                schema.execute = json.get("execute").cloned();
+                schema.query = json.get("query").cloned();
+                if let Some(responses) = json["responses"].as_object() {
+                    schema.responses = responses.clone();
+                }
+                continue;
+            }
+
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default();
+            match stem {
+                "instantiate_msg" | "init_msg" => schema.instantiate = Some(json),
+                "execute_msg" | "handle_msg" => schema.execute = Some(json),
+                "query_msg" => schema.query = Some(json),
+                // Older layouts name responses after their query, ex. `balance_response.json`
+                _ => {
+                    if let Some(query) = stem.strip_suffix("_response") {
+                        schema.responses.insert(query.to_string(), json);
+                    }
+                }
+            }
+        }
+
+        if schema.instantiate.is_none() && schema.execute.is_none() && schema.query.is_none() {
+            anyhow::bail!("No message schemas found in {}", dir.display());
+        }
+
+        if let Some(root) = &schema.instantiate {
+            schema.variants.push(Variant {
+                kind: MsgKind::Instantiate,
+                name: "instantiate".to_string(),
+                fields: fields(root, root),
+                unit: false,
+                response: vec![],
+            });
+        }
+        for (kind, root) in [
+            (MsgKind::Execute, &schema.execute),
+            (MsgKind::Query, &schema.query),
+        ] {
+            if let Some(root) = root {
+                schema.variants.extend(variants(kind, root));
+            }
+        }
+
+        for variant in schema.variants.iter_mut() {
+            if variant.kind == MsgKind::Query {
+                if let Some(response) = schema.responses.get(&variant.name) {
+                    variant.response = fields(response, response);
+                }
+            }
+        }
+
+        Ok(schema)
+    }
+
+    pub fn root(&self, kind: MsgKind) -> Option<&Value> {
+        match kind {
+            MsgKind::Instantiate => self.instantiate.as_ref(),
+            MsgKind::Execute => self.execute.as_ref(),
+            MsgKind::Query => self.query.as_ref(),
+        }
+    }
+
+    /// Checks the message against the schema before it's sent
+    pub fn validate(&self, kind: MsgKind, msg: &Value) -> anyhow::Result<()> {
+        let root = self
+            .root(kind)
+            .ok_or_else(|| anyhow!("Schema has no {:?} message", kind))?;
+        validate(root, root, msg, "msg")
+    }
+}
+
+/// Follows `$ref`s and the `allOf` wrappers schemars adds around documented refs
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let pointer = reference.trim_start_matches('#');
+        if let Some(schema) = root.pointer(pointer) {
+            return resolve(root, schema);
+        }
+    }
+    match schema["allOf"].as_array().map(|all| all.as_slice()) {
+        Some([schema]) => resolve(root, schema),
+        _ => schema,
+    }
+}
+
+fn variants(kind: MsgKind, root: &Value) -> Vec<Variant> {
+    let options = root["oneOf"]
+        .as_array()
+        .or_else(|| root["anyOf"].as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut variants = vec![];
+    for option in options.iter() {
+        let option = resolve(root, option);
+
+        // Unit variants are string enums
+        if let Some(names) = option["enum"].as_array() {
+            for name in names.iter().filter_map(|name| name.as_str()) {
+                variants.push(Variant {
+                    kind,
+                    name: name.to_string(),
+                    fields: vec![],
+                    unit: true,
+                    response: vec![],
+                });
+            }
+            continue;
+        }
+
+        if let Some(properties) = option["properties"].as_object() {
+            for (name, body) in properties.iter() {
+                variants.push(Variant {
+                    kind,
+                    name: name.clone(),
+                    fields: fields(root, body),
+                    unit: false,
+                    response: vec![],
+                });
+            }
+        }
+    }
+    variants
+}
+
+/// Typed fields of an object schema
+fn fields(root: &Value, schema: &Value) -> Vec<(String, DataType)> {
+    let schema = resolve(root, schema);
+    schema["properties"]
+        .as_object()
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, field)| (name.clone(), data_type(root, field)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Closest port type for a schema
+pub fn data_type(root: &Value, schema: &Value) -> DataType {
+    // Documented fields wrap their ref in a single allOf
+    let mut schema = schema;
+    while let Some([inner]) = schema["allOf"].as_array().map(|all| all.as_slice()) {
+        schema = inner;
+    }
+
+    // Some cosmwasm types are refs to strings we have a better type for
+    match schema["$ref"].as_str() {
+        Some("#/definitions/Binary") => return DataType::Binary,
+        Some("#/definitions/Coin") => return DataType::Coin,
+        _ => {}
+    }
+
+    let schema = resolve(root, schema);
+
+    // Option<T> is written as anyOf [T, null]
+    if let Some(options) = schema["anyOf"].as_array() {
+        let not_null: Vec<_> = options
+            .iter()
+            .filter(|option| option["type"] != "null")
+            .collect();
+        if let [option] = not_null.as_slice() {
+            let data = data_type(root, option);
+            return if not_null.len() < options.len() {
+                DataType::Optional(Box::new(data))
+            } else {
+                data
+            };
+        }
+        return DataType::Json;
+    }
+
+    // Or as type [T, "null"]
+    let (typ, optional) = match &schema["type"] {
+        Value::String(typ) => (typ.as_str(), false),
+        Value::Array(types) => {
+            let not_null: Vec<_> = types
+                .iter()
+                .filter_map(|typ| typ.as_str())
+                .filter(|typ| *typ != "null")
+                .collect();
+            match not_null.as_slice() {
+                [typ] => (*typ, not_null.len() < types.len()),
+                _ => ("object", false),
+            }
+        }
+        _ => ("object", false),
+    };
+
+    let data = match typ {
+        "boolean" => DataType::Bool,
+        "integer" | "number" => DataType::Number,
+        "string" => DataType::String,
+        "array" => DataType::Array(Box::new(data_type(root, &schema["items"]))),
+        _ => DataType::Json,
+    };
+
+    if optional {
+        DataType::Optional(Box::new(data))
+    } else {
+        data
+    }
+}
+
+fn type_matches(typ: &str, value: &Value) -> bool {
+    match typ {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+/// Subset of json schema validation covering what schemars generates
+fn validate(root: &Value, schema: &Value, value: &Value, path: &str) -> anyhow::Result<()> {
+    let schema = resolve(root, schema);
+
+    if let Some(options) = schema["anyOf"]
+        .as_array()
+        .or_else(|| schema["oneOf"].as_array())
+    {
+        let mut errors = vec![];
+        for option in options.iter() {
+            match validate(root, option, value, path) {
+                Ok(()) => return Ok(()),
+                Err(err) => errors.push(err.to_string()),
+            }
+        }
+        anyhow::bail!(
+            "{} matches none of the options: {}",
+            path,
+            errors.join("; ")
+        );
+    }
+
+    match &schema["type"] {
+        Value::String(typ) if !type_matches(typ, value) => {
+            anyhow::bail!("{} should be a {}", path, typ)
+        }
+        Value::Array(types)
+            if !types
+                .iter()
+                .filter_map(|typ| typ.as_str())
+                .any(|typ| type_matches(typ, value)) =>
+        {
+            anyhow::bail!("{} should be one of {}", path, Value::Array(types.clone()))
+        }
+        _ => {}
+    }
+
+    if let Some(options) = schema["enum"].as_array() {
+        if !options.contains(value) {
+            anyhow::bail!("{} should be one of {}", path, schema["enum"]);
+        }
+    }
+
+    if let Some(minimum) = schema["minimum"].as_f64() {
+        if value.as_f64().map(|n| n < minimum).unwrap_or(false) {
+            anyhow::bail!("{} should be at least {}", path, minimum);
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(required) = schema["required"].as_array() {
+            for key in required.iter().filter_map(|key| key.as_str()) {
+                if !object.contains_key(key) {
+                    anyhow::bail!("{} is missing {}", path, key);
+                }
+            }
+        }
+
+        let properties = schema["properties"].as_object();
+        for (key, field) in object.iter() {
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property) => validate(root, property, field, &format!("{}.{}", path, key))?,
+                None if schema["additionalProperties"] == false => {
+                    anyhow::bail!("{} has unknown field {}", path, key)
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item)) = (value, schema.get("items")) {
+        for (i, value) in items.iter().enumerate() {
+            validate(root, item, value, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn documented_refs_keep_their_type() {
+        let root = json!({
+            "definitions": {
+                "Binary": { "type": "string" },
+                "Coin": { "type": "object" },
+            }
+        });
+        let documented =
+            |reference: &str| json!({ "description": "docs", "allOf": [{ "$ref": reference }] });
+
+        assert_eq!(
+            data_type(&root, &documented("#/definitions/Binary")),
+            DataType::Binary
+        );
+        assert_eq!(
+            data_type(&root, &documented("#/definitions/Coin")),
+            DataType::Coin
+        );
+        assert_eq!(
+            data_type(&root, &json!({ "$ref": "#/definitions/Coin" })),
+            DataType::Coin
+        );
+    }
+
+    #[test]
+    fn optional_documented_refs() {
+        let root = json!({ "definitions": { "Binary": { "type": "string" } } });
+        let schema = json!({
+            "anyOf": [{ "allOf": [{ "$ref": "#/definitions/Binary" }] }, { "type": "null" }]
+        });
+        assert!(data_type(&root, &schema).is(&DataType::Optional(Box::new(DataType::Binary))));
+    }
+}
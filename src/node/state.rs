@@ -1,4 +1,5 @@
 use crate::node::data::{DataType, ValueType};
+use crate::node::schema::{ContractSchema, MsgKind, Variant};
 use crate::node::template::Template;
 use crate::node::{GraphState, Response};
 use eframe::egui;
@@ -9,6 +10,16 @@ use egui_node_graph::{Graph, NodeDataTrait, NodeId, NodeResponse, UserResponseTr
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeState {
     pub(crate) template: Template,
+    /// Splitters, Array Get and For Each error on missing or mistyped values instead of coercing them
+    pub(crate) strict: bool,
+    /// Pauses the debugger before evaluating this node
+    pub(crate) breakpoint: bool,
+    /// Only pause when this holds, see `debugger::condition_met`
+    pub(crate) condition: String,
+    /// Contract schema the msg ports are generated from
+    pub(crate) schema: Option<ContractSchema>,
+    /// Selected msg, its fields replace the raw json inputs
+    pub(crate) variant: Option<Variant>,
 }
 
 impl NodeDataTrait for NodeState {
@@ -29,11 +40,41 @@ impl NodeDataTrait for NodeState {
     {
         let mut responses = vec![];
 
+        if let Some(debugger) = &user_state.debugger {
+            if debugger.current() == Some(node_id) {
+                ui.colored_label(egui::Color32::GOLD, "⏸ Paused here");
+                for (name, value) in debugger.inputs.iter() {
+                    match value {
+                        Some(value) => ui.label(format!("{}: {:?}", name, value)),
+                        None => ui.label(format!("{}: not evaluated", name)),
+                    };
+                }
+                if let Some(err) = &debugger.error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+            }
+        }
+
         let is_editing = user_state
             .editing_node
             .map(|id| id == node_id)
             .unwrap_or(false);
 
+        if self.template == Template::DeconstructJson
+            || matches!(
+                self.template,
+                Template::DeconstructArray(_)
+                    | Template::Extract(_)
+                    | Template::ArrayGet(_)
+                    | Template::ForEach(..)
+            )
+        {
+            let mut strict = self.strict;
+            if ui.checkbox(&mut strict, "Strict").changed() {
+                responses.push(NodeResponse::User(Response::ToggleStrict(node_id)));
+            }
+        }
+
         if graph[node_id].user_data.template.is_json()
             || graph[node_id].user_data.template.is_array()
         {
@@ -47,7 +88,7 @@ impl NodeDataTrait for NodeState {
                         ui.add(TextEdit::singleline(&mut user_state.json_name));
                         user_state.json_name = user_state.json_name.replace(" ", "_");
                     }
-                    user_state.new_type.combo_box(ui);
+                    user_state.new_type.combo_box(ui, "new_type");
                     if graph[node_id].user_data.template.is_array() {
                         if ui.button("Update").clicked() {
                             responses.push(NodeResponse::User(Response::UpdateArrayType(node_id)))
@@ -68,6 +109,135 @@ impl NodeDataTrait for NodeState {
             }
         }
 
+        if !self.template.port_types().is_empty() {
+            if !is_editing {
+                if ui.button("Edit types").clicked() {
+                    responses.push(NodeResponse::User(Response::SetEditingNode(node_id)))
+                }
+            } else {
+                ui.horizontal(|ui| {
+                    for (i, data) in user_state.port_types.iter_mut().enumerate() {
+                        data.combo_box(ui, ("port_type", i));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Update").clicked() {
+                        responses.push(NodeResponse::User(Response::UpdatePortTypes(node_id)))
+                    }
+                    if ui.button("Done").clicked() {
+                        responses.push(NodeResponse::User(Response::ClearEditingNode))
+                    }
+                });
+            }
+        }
+
+        if self.template.uses_schema() {
+            if !is_editing {
+                ui.horizontal(|ui| {
+                    match (&self.schema, &self.variant) {
+                        (Some(_), Some(variant)) => ui.label(variant.label()),
+                        (Some(schema), None) => ui.label(format!("{} schema", schema.name)),
+                        (None, _) => ui.weak("No schema"),
+                    };
+                    if ui.button("Schema").clicked() {
+                        responses.push(NodeResponse::User(Response::SetEditingNode(node_id)))
+                    }
+                });
+            } else {
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(&mut user_state.schema_path).hint_text("schema"));
+                    if ui.button("Load").clicked() {
+                        responses.push(NodeResponse::User(Response::LoadSchema(node_id)))
+                    }
+                    if self.schema.is_some() && ui.button("Unload").clicked() {
+                        responses.push(NodeResponse::User(Response::UnloadSchema(node_id)))
+                    }
+                });
+
+                // The instantiate msg has no variants to pick from
+                if let (Some(schema), Template::ConstructMsg) = (&self.schema, &self.template) {
+                    let selected = self
+                        .variant
+                        .as_ref()
+                        .map(|variant| variant.label())
+                        .unwrap_or_else(|| "type and json".to_string());
+                    egui::ComboBox::from_id_source(("schema_variant", node_id))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            if ui
+                                .selectable_label(self.variant.is_none(), "type and json")
+                                .clicked()
+                            {
+                                responses.push(NodeResponse::User(Response::SelectVariant(
+                                    node_id, None,
+                                )))
+                            }
+                            for (i, variant) in schema.variants.iter().enumerate() {
+                                if variant.kind == MsgKind::Instantiate {
+                                    continue;
+                                }
+                                let checked = self.variant.as_ref() == Some(variant);
+                                if ui.selectable_label(checked, variant.label()).clicked() {
+                                    responses.push(NodeResponse::User(Response::SelectVariant(
+                                        node_id,
+                                        Some(i),
+                                    )))
+                                }
+                            }
+                        });
+                }
+
+                if let Some(err) = &user_state.schema_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                if ui.button("Done").clicked() {
+                    responses.push(NodeResponse::User(Response::ClearEditingNode))
+                }
+            }
+        }
+
+        if self.template == Template::DeploymentOutput {
+            if ui.button("💾 Write").clicked() {
+                responses.push(NodeResponse::User(Response::WriteDeployment(node_id)));
+            }
+        }
+
+        if !self.breakpoint {
+            if ui.button("⏺ Breakpoint").clicked() {
+                responses.push(NodeResponse::User(Response::ToggleBreakpoint(node_id)));
+            }
+        } else {
+            let button =
+                egui::Button::new(egui::RichText::new("⏺ Breakpoint").color(egui::Color32::WHITE))
+                    .fill(egui::Color32::DARK_RED);
+            if ui.add(button).clicked() {
+                responses.push(NodeResponse::User(Response::ToggleBreakpoint(node_id)));
+            }
+
+            let is_editing_condition = user_state
+                .condition_node
+                .map(|id| id == node_id)
+                .unwrap_or(false);
+
+            ui.horizontal(|ui| {
+                if is_editing_condition {
+                    ui.add(TextEdit::singleline(&mut user_state.condition).hint_text("condition"));
+                    if ui.button("Set").clicked() {
+                        responses.push(NodeResponse::User(Response::SetCondition(node_id)));
+                    }
+                } else {
+                    if self.condition.is_empty() {
+                        ui.label("Always pause");
+                    } else {
+                        ui.label(format!("Pause if {}", self.condition));
+                    }
+                    if ui.button("Edit").clicked() {
+                        responses.push(NodeResponse::User(Response::EditCondition(node_id)));
+                    }
+                }
+            });
+        }
+
         let is_active = user_state
             .active_node
             .map(|id| id == node_id)
@@ -0,0 +1,221 @@
+use crate::node::data::DataType;
+use crate::node::fragment::Fragment;
+use crate::node::NodeGraph;
+use eframe::egui;
+use egui_node_graph::{InputId, NodeId, OutputId};
+
+/// Nodes collapsed into a single reusable node
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Subgraph {
+    pub name: String,
+    pub fragment: Fragment,
+    pub inputs: Vec<Port>,
+    pub outputs: Vec<Port>,
+}
+
+/// A param of a node inside the subgraph exposed on the subgraph node
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Port {
+    pub name: String,
+    pub data: DataType,
+    /// Index into `Fragment::nodes`
+    pub node: usize,
+    pub param: String,
+}
+
+/// Connections that crossed the selection, by the port they now go through
+pub struct Boundary {
+    pub inputs: Vec<(OutputId, String)>,
+    pub outputs: Vec<(String, InputId)>,
+}
+
+impl Subgraph {
+    /// Inputs connected from outside of the nodes become the subgraph inputs, outputs connected
+    /// to the outside its outputs. With nothing connected outside, all outputs unused inside are.
+    pub fn collapse(
+        name: String,
+        graph: &NodeGraph,
+        nodes: &[NodeId],
+        position: impl Fn(NodeId) -> egui::Pos2,
+    ) -> (Self, Boundary) {
+        let fragment = Fragment::from_nodes(graph, nodes, position);
+        let index = |id: NodeId| nodes.iter().position(|node| *node == id);
+
+        let mut inputs: Vec<Port> = vec![];
+        let mut boundary = Boundary {
+            inputs: vec![],
+            outputs: vec![],
+        };
+        for (node, id) in nodes.iter().enumerate() {
+            for (param, input) in graph[*id].inputs.iter() {
+                let output = match graph.connection(*input) {
+                    Some(output) if index(graph[output].node).is_none() => output,
+                    _ => continue,
+                };
+                let name = unique_name(&inputs, param);
+                inputs.push(Port {
+                    name: name.clone(),
+                    data: graph[*input].typ.clone(),
+                    node,
+                    param: param.clone(),
+                });
+                boundary.inputs.push((output, name));
+            }
+        }
+
+        let mut outputs: Vec<Port> = vec![];
+        for (input, output) in graph.iter_connections() {
+            let node = match (index(graph[output].node), index(graph[input].node)) {
+                (Some(node), None) => node,
+                _ => continue,
+            };
+            let param = output_name(graph, output);
+            let existing = outputs
+                .iter()
+                .find(|port| port.node == node && port.param == param);
+            let name = match existing {
+                Some(port) => port.name.clone(),
+                None => {
+                    let name = unique_name(&outputs, &param);
+                    outputs.push(Port {
+                        name: name.clone(),
+                        data: graph[output].typ.clone(),
+                        node,
+                        param,
+                    });
+                    name
+                }
+            };
+            boundary.outputs.push((name, input));
+        }
+
+        if outputs.is_empty() {
+            for (node, id) in nodes.iter().enumerate() {
+                for (param, output) in graph[*id].outputs.iter() {
+                    let used = fragment
+                        .connections
+                        .iter()
+                        .any(|connection| connection.output == (node, param.clone()));
+                    if !used {
+                        outputs.push(Port {
+                            name: unique_name(&outputs, param),
+                            data: graph[*output].typ.clone(),
+                            node,
+                            param: param.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let subgraph = Self {
+            name,
+            fragment,
+            inputs,
+            outputs,
+        };
+        (subgraph, boundary)
+    }
+}
+
+fn output_name(graph: &NodeGraph, output: OutputId) -> String {
+    graph[graph[output].node]
+        .outputs
+        .iter()
+        .find(|(_, id)| *id == output)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_default()
+}
+
+/// Several nodes can have params with the same name
+fn unique_name(ports: &[Port], name: &str) -> String {
+    let mut unique = name.to_string();
+    let mut i = 1;
+    while ports.iter().any(|port| port.name == unique) {
+        i += 1;
+        unique = format!("{}_{}", name, i);
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::math::MathOp;
+    use crate::node::template::Template;
+    use crate::node::GraphState;
+    use egui_node_graph::NodeTemplateTrait;
+
+    fn add(graph: &mut NodeGraph, template: Template) -> NodeId {
+        let mut user_state = GraphState::default();
+        let label = template.node_graph_label(&mut user_state);
+        let user_data = template.user_data(&mut user_state);
+        graph.add_node(label, user_data, |graph, id| {
+            template.build_node(graph, &mut user_state, id)
+        })
+    }
+
+    fn connect(graph: &mut NodeGraph, from: NodeId, output: &str, to: NodeId, input: &str) {
+        let output = graph[from].get_output(output).unwrap();
+        let input = graph[to].get_input(input).unwrap();
+        graph.add_connection(output, input);
+    }
+
+    fn collapse(graph: &NodeGraph, nodes: &[NodeId]) -> (Subgraph, Boundary) {
+        Subgraph::collapse("sum".to_string(), graph, nodes, |_| egui::Pos2::default())
+    }
+
+    fn ports(ports: &[Port]) -> Vec<(&str, usize, &str)> {
+        ports
+            .iter()
+            .map(|port| (port.name.as_str(), port.node, port.param.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn outside_connections_become_ports() {
+        let mut graph = NodeGraph::new();
+        let x = add(&mut graph, Template::MakeNumber);
+        let y = add(&mut graph, Template::MakeNumber);
+        let first = add(&mut graph, Template::Math(MathOp::Add));
+        let second = add(&mut graph, Template::Math(MathOp::Add));
+        let after = add(&mut graph, Template::Math(MathOp::Mul));
+        connect(&mut graph, x, "out", first, "a");
+        connect(&mut graph, y, "out", second, "a");
+        connect(&mut graph, first, "out", second, "b");
+        connect(&mut graph, second, "out", after, "a");
+        connect(&mut graph, second, "out", after, "b");
+
+        let (subgraph, boundary) = collapse(&graph, &[first, second]);
+
+        // Same named params get unique ports, unconnected inputs stay inside
+        assert_eq!(
+            ports(&subgraph.inputs),
+            vec![("a", 0, "a"), ("a_2", 1, "a")]
+        );
+        // An output used twice outside is a single port
+        assert_eq!(ports(&subgraph.outputs), vec![("out", 1, "out")]);
+        assert_eq!(boundary.inputs.len(), 2);
+        assert_eq!(boundary.outputs.len(), 2);
+        assert!(boundary.outputs.iter().all(|(name, _)| name == "out"));
+        assert_eq!(subgraph.fragment.connections.len(), 1);
+    }
+
+    #[test]
+    fn unused_outputs_are_exposed_without_outside_connections() {
+        let mut graph = NodeGraph::new();
+        let first = add(&mut graph, Template::Math(MathOp::Add));
+        let second = add(&mut graph, Template::Not);
+        let third = add(&mut graph, Template::Math(MathOp::Sub));
+        connect(&mut graph, first, "out", third, "a");
+
+        let (subgraph, boundary) = collapse(&graph, &[first, second, third]);
+
+        assert!(subgraph.inputs.is_empty());
+        assert_eq!(
+            ports(&subgraph.outputs),
+            vec![("out", 1, "out"), ("out_2", 2, "out")]
+        );
+        assert!(boundary.inputs.is_empty() && boundary.outputs.is_empty());
+    }
+}
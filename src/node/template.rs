@@ -1,23 +1,64 @@
 use crate::node::data::{DataType, ValueType};
+use crate::node::format::TEMPLATE_INPUT;
+use crate::node::math::{CompareOp, MathOp};
+use crate::node::schema::{ContractSchema, MsgKind, Variant};
+use crate::node::subgraph::Subgraph;
+use crate::node::wasm::DEFAULT_MAX_WASM_SIZE;
 use crate::node::{GraphState, NodeGraph, NodeState};
 use egui_node_graph::{Graph, InputParamKind, NodeId, NodeTemplateIter, NodeTemplateTrait};
-use serde_json::{Number, Value};
+use serde_json::Number;
 use std::borrow::Cow;
+use std::rc::Rc;
+use std::str::FromStr;
 
 /// Represents the different supported node types
-#[derive(Clone, Copy, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Template {
     // TODO: add constant templates; SaveConstant, LoadConstant, where a global state stores a Map<String, NodeId>
     MakeBool,
     MakeNumber,
     MakeString,
+    MakeCoin,
+    /// Interpolates `{{name}}` placeholders, each one becomes a string input
+    FormatString,
+
+    Math(MathOp),
+    Compare(CompareOp),
+
+    And,
+    Or,
+    Not,
+    /// Picks between then and else, only the taken branch is evaluated
+    Select(DataType),
 
     ConstructArray(DataType),
     DeconstructArray(DataType),
+    ArrayLength(DataType),
+    /// Negative indexes count from the end
+    ArrayGet(DataType),
+    ArrayConcat(DataType),
+    ArraySlice(DataType),
+    /// Evaluates the nodes connected between element and result once per element
+    ForEach(DataType, DataType),
 
     ConstructJson,
     DeconstructJson,
+    /// Selects a nested value by JSON Pointer or JSONPath, see `path::select`
+    Extract(DataType),
+    /// Deep merges the overlay into the base
+    MergeJson,
+    /// Applies an RFC 6902 JSON Patch
+    PatchJson,
+    SetJson,
+
+    /// Explicit coercion from the first type into the second, see `ValueType::coerce`
+    Convert(DataType, DataType),
+    JsonToBinary,
+    BinaryToJson,
+    StringToBinary,
+    BinaryToString,
+    HexToBinary,
+    BinaryToHex,
 
     Account,
     Store,
@@ -26,12 +67,33 @@ pub enum Template {
     DeconstructMsg,
     ExecuteContract,
     QueryContract,
+    /// Loads a contract schema, its msgs become `ContractCall` templates
+    ContractInterface,
+    /// Execute or query msg of a loaded contract interface, named by the contract
+    ContractCall(String, Variant),
+
+    /// Collapsed nodes, see `Subgraph::collapse`
+    Subgraph(Box<Subgraph>),
+
+    DeploymentOutput,
+    WasmChecksum,
 }
 
 impl Template {
+    /// Nodes whose params are named by the user
     pub fn is_json(&self) -> bool {
         match self {
-            Template::ConstructJson | Template::DeconstructJson => true,
+            Template::ConstructJson | Template::DeconstructJson | Template::DeploymentOutput => {
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Nodes that can generate their msg ports from a contract schema
+    pub fn uses_schema(&self) -> bool {
+        match self {
+            Template::ConstructMsg | Template::Instantiate | Template::ContractInterface => true,
             _ => false,
         }
     }
@@ -42,6 +104,45 @@ impl Template {
             _ => false,
         }
     }
+
+    /// Types the user picks for the node ports
+    pub fn port_types(&self) -> Vec<DataType> {
+        match self {
+            Template::Convert(from, to) => vec![from.clone(), to.clone()],
+            Template::Extract(data) => vec![data.clone()],
+            Template::Select(data) => vec![data.clone()],
+            Template::ArrayLength(data)
+            | Template::ArrayGet(data)
+            | Template::ArrayConcat(data)
+            | Template::ArraySlice(data) => vec![data.clone()],
+            Template::ForEach(from, to) => vec![from.clone(), to.clone()],
+            _ => vec![],
+        }
+    }
+
+    /// Inputs only evaluated on demand, the debugger only steps into them once the node needs them
+    pub fn lazy_inputs(&self) -> &'static [&'static str] {
+        match self {
+            Template::Select(_) => &["then", "else"],
+            Template::ForEach(..) => &["result"],
+            _ => &[],
+        }
+    }
+
+    /// Same node with the new port types, see `port_types`
+    pub fn with_port_types(&self, types: &[DataType]) -> Template {
+        match (self, types) {
+            (Template::Convert(..), [from, to]) => Template::Convert(from.clone(), to.clone()),
+            (Template::Extract(_), [data]) => Template::Extract(data.clone()),
+            (Template::Select(_), [data]) => Template::Select(data.clone()),
+            (Template::ArrayLength(_), [data]) => Template::ArrayLength(data.clone()),
+            (Template::ArrayGet(_), [data]) => Template::ArrayGet(data.clone()),
+            (Template::ArrayConcat(_), [data]) => Template::ArrayConcat(data.clone()),
+            (Template::ArraySlice(_), [data]) => Template::ArraySlice(data.clone()),
+            (Template::ForEach(..), [from, to]) => Template::ForEach(from.clone(), to.clone()),
+            _ => self.clone(),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -61,13 +162,7 @@ pub fn add_param(id: NodeId, param_type: DataType, name: &str, io: IO, graph: &m
                 .is_some();
 
             if !is_duplicate {
-                let value = match param_type {
-                    DataType::Bool => ValueType::Bool(true),
-                    DataType::Number => ValueType::Number(Number::from(0)),
-                    DataType::String => ValueType::String("".to_string()),
-                    DataType::Array => ValueType::Array(vec![]),
-                    DataType::Json => ValueType::Json(Value::default()),
-                };
+                let value = param_type.input_value();
 
                 graph.add_input_param(
                     id,
@@ -118,6 +213,52 @@ pub fn remove_param(id: NodeId, name: &str, io: IO, graph: &mut NodeGraph) {
     }
 }
 
+/// Gas and fee settings shared by the nodes that broadcast a tx.
+/// Gas is either a limit or `auto`, which simulates and applies the multiplier.
+/// Gas price is the amount per unit of gas paid in the fee denom.
+/// The tx output is the signed tx a dry run simulated instead of broadcasting.
+fn add_fee_params(id: NodeId, graph: &mut NodeGraph) {
+    let decimal = |text: &str| ValueType::Number(Number::from_str(text).expect("valid decimal"));
+
+    add_param(id, DataType::String, "gas", IO::Input, graph);
+    add_param(id, DataType::Number, "gas_multiplier", IO::Input, graph);
+    add_param(id, DataType::Number, "gas_price", IO::Input, graph);
+    add_param(id, DataType::String, "fee_denom", IO::Input, graph);
+
+    for (name, value) in [
+        ("gas", ValueType::String("auto".to_string())),
+        ("gas_multiplier", decimal("1.3")),
+        ("gas_price", decimal("0.25")),
+        ("fee_denom", ValueType::String("uscrt".to_string())),
+    ] {
+        if let Ok(input) = graph[id].get_input(name) {
+            graph[input].value = value;
+        }
+    }
+
+    // Null when no tx was broadcast, the result is what the msg returned
+    for (name, data) in [
+        ("gas_used", DataType::Number),
+        ("fee", DataType::Coin),
+        ("tx", DataType::Json),
+        ("result", DataType::Json),
+    ] {
+        add_param(
+            id,
+            DataType::Optional(Box::new(data)),
+            name,
+            IO::Output,
+            graph,
+        );
+    }
+}
+
+/// Coins sent along with the message, left empty when nothing is sent
+fn add_funds_param(id: NodeId, graph: &mut NodeGraph) {
+    let coins = DataType::Array(Box::new(DataType::Coin));
+    add_param(id, coins, "funds", IO::Input, graph);
+}
+
 impl NodeTemplateTrait for Template {
     type NodeData = NodeState;
     type DataType = DataType;
@@ -126,15 +267,45 @@ impl NodeTemplateTrait for Template {
 
     fn node_finder_label(&self, _user_state: &mut Self::UserState) -> Cow<str> {
         Cow::Borrowed(match self {
+            Template::ContractCall(contract, variant) => {
+                return Cow::Owned(format!("{} {}", contract, variant.label()))
+            }
+            Template::Subgraph(subgraph) => return Cow::Borrowed(&subgraph.name),
+
             Template::MakeBool => "Boolean",
             Template::MakeNumber => "Number",
             Template::MakeString => "String",
+            Template::MakeCoin => "Coin",
+            Template::FormatString => "String Template",
+            Template::Math(op) => op.name(),
+            Template::Compare(op) => op.name(),
+            Template::And => "And",
+            Template::Or => "Or",
+            Template::Not => "Not",
+            Template::Select(_) => "Select",
 
             Template::ConstructArray(_) => "Array Constructor",
             Template::DeconstructArray(_) => "Array Splitter",
+            Template::ArrayLength(_) => "Array Length",
+            Template::ArrayGet(_) => "Array Get",
+            Template::ArrayConcat(_) => "Array Concat",
+            Template::ArraySlice(_) => "Array Slice",
+            Template::ForEach(..) => "For Each",
 
             Template::ConstructJson => "Json Constructor",
             Template::DeconstructJson => "Json Splitter",
+            Template::Extract(_) => "Json Extract",
+            Template::MergeJson => "Json Merge",
+            Template::PatchJson => "Json Patch",
+            Template::SetJson => "Json Set",
+
+            Template::Convert(..) => "Convert",
+            Template::JsonToBinary => "Json to Binary",
+            Template::BinaryToJson => "Binary to Json",
+            Template::StringToBinary => "String to Binary",
+            Template::BinaryToString => "Binary to String",
+            Template::HexToBinary => "Hex to Binary",
+            Template::BinaryToHex => "Binary to Hex",
 
             Template::Account => "Account",
             Template::Store => "Store Contract",
@@ -143,6 +314,10 @@ impl NodeTemplateTrait for Template {
             Template::DeconstructMsg => "Msg Splitter",
             Template::ExecuteContract => "Execute Contract",
             Template::QueryContract => "Query Contract",
+            Template::ContractInterface => "Contract Interface",
+
+            Template::DeploymentOutput => "Deployment Output",
+            Template::WasmChecksum => "Wasm Checksum",
         })
     }
 
@@ -150,8 +325,28 @@ impl NodeTemplateTrait for Template {
         self.node_finder_label(user_state).into()
     }
 
-    fn user_data(&self, _user_state: &mut Self::UserState) -> Self::NodeData {
-        NodeState { template: *self }
+    fn user_data(&self, user_state: &mut Self::UserState) -> Self::NodeData {
+        // Calls keep their interface's schema to validate msgs against
+        let (schema, variant) = match self {
+            Template::ContractCall(contract, variant) => (
+                user_state
+                    .interfaces
+                    .iter()
+                    .find(|schema| schema.name == *contract)
+                    .cloned(),
+                Some(variant.clone()),
+            ),
+            _ => (None, None),
+        };
+
+        NodeState {
+            template: self.clone(),
+            strict: false,
+            breakpoint: false,
+            condition: String::new(),
+            schema,
+            variant,
+        }
     }
 
     /// Only runs at node creation
@@ -174,13 +369,100 @@ impl NodeTemplateTrait for Template {
                 add_param(node_id, DataType::String, "string", IO::Input, graph);
                 add_param(node_id, DataType::String, "out", IO::Output, graph);
             }
+            Template::MakeCoin => {
+                add_param(node_id, DataType::String, "amount", IO::Input, graph);
+                add_param(node_id, DataType::String, "denom", IO::Input, graph);
+                if let Ok(input) = graph[node_id].get_input("denom") {
+                    graph[input].value = ValueType::String("uscrt".to_string());
+                }
+                add_param(node_id, DataType::Coin, "out", IO::Output, graph);
+            }
+            Template::Math(_) => {
+                add_param(node_id, DataType::Number, "a", IO::Input, graph);
+                add_param(node_id, DataType::Number, "b", IO::Input, graph);
+                add_param(node_id, DataType::Number, "out", IO::Output, graph);
+            }
+            Template::Compare(_) => {
+                add_param(node_id, DataType::Number, "a", IO::Input, graph);
+                add_param(node_id, DataType::Number, "b", IO::Input, graph);
+                add_param(node_id, DataType::Bool, "out", IO::Output, graph);
+            }
+            Template::And | Template::Or => {
+                add_param(node_id, DataType::Bool, "a", IO::Input, graph);
+                add_param(node_id, DataType::Bool, "b", IO::Input, graph);
+                add_param(node_id, DataType::Bool, "out", IO::Output, graph);
+            }
+            Template::Not => {
+                add_param(node_id, DataType::Bool, "bool", IO::Input, graph);
+                add_param(node_id, DataType::Bool, "out", IO::Output, graph);
+            }
+            Template::Select(data) => {
+                add_param(node_id, DataType::Bool, "cond", IO::Input, graph);
+                add_param(node_id, data.clone(), "then", IO::Input, graph);
+                add_param(node_id, data.clone(), "else", IO::Input, graph);
+                add_param(node_id, data.clone(), "out", IO::Output, graph);
+            }
+            Template::FormatString => {
+                // Placeholder inputs are added as the template is typed
+                add_param(node_id, DataType::String, TEMPLATE_INPUT, IO::Input, graph);
+                add_param(node_id, DataType::String, "out", IO::Output, graph);
+            }
 
-            Template::ConstructArray(_) => {
-                add_param(node_id, DataType::Array, "out", IO::Output, graph);
+            Template::ConstructArray(data) => {
+                let array = DataType::Array(Box::new(data.clone()));
+                add_param(node_id, array, "out", IO::Output, graph);
             }
 
-            Template::DeconstructArray(_) => {
-                add_param(node_id, DataType::Array, "array", IO::Input, graph);
+            Template::DeconstructArray(data) => {
+                let array = DataType::Array(Box::new(data.clone()));
+                add_param(node_id, array, "array", IO::Input, graph);
+            }
+            Template::ArrayLength(data) => {
+                let array = DataType::Array(Box::new(data.clone()));
+                add_param(node_id, array, "array", IO::Input, graph);
+                add_param(node_id, DataType::Number, "length", IO::Output, graph);
+            }
+            Template::ArrayGet(data) => {
+                let array = DataType::Array(Box::new(data.clone()));
+                add_param(node_id, array, "array", IO::Input, graph);
+                add_param(node_id, DataType::Number, "index", IO::Input, graph);
+                add_param(node_id, data.clone(), "out", IO::Output, graph);
+            }
+            Template::ArrayConcat(data) => {
+                let array = DataType::Array(Box::new(data.clone()));
+                add_param(node_id, array.clone(), "a", IO::Input, graph);
+                add_param(node_id, array.clone(), "b", IO::Input, graph);
+                add_param(node_id, array, "out", IO::Output, graph);
+            }
+            Template::ArraySlice(data) => {
+                let array = DataType::Array(Box::new(data.clone()));
+                add_param(node_id, array.clone(), "array", IO::Input, graph);
+                add_param(node_id, DataType::Number, "start", IO::Input, graph);
+                // Slices to the end when empty
+                let end = DataType::Optional(Box::new(DataType::Number));
+                add_param(node_id, end, "end", IO::Input, graph);
+                add_param(node_id, array, "out", IO::Output, graph);
+            }
+            Template::ForEach(from, to) => {
+                add_param(
+                    node_id,
+                    DataType::Array(Box::new(from.clone())),
+                    "array",
+                    IO::Input,
+                    graph,
+                );
+                // Connected back from the nodes using element and index
+                add_param(node_id, to.clone(), "result", IO::Input, graph);
+
+                add_param(node_id, from.clone(), "element", IO::Output, graph);
+                add_param(node_id, DataType::Number, "index", IO::Output, graph);
+                add_param(
+                    node_id,
+                    DataType::Array(Box::new(to.clone())),
+                    "out",
+                    IO::Output,
+                    graph,
+                );
             }
 
             Template::ConstructJson => {
@@ -189,6 +471,59 @@ impl NodeTemplateTrait for Template {
             Template::DeconstructJson => {
                 add_param(node_id, DataType::Json, "json", IO::Input, graph);
             }
+            Template::Extract(data) => {
+                add_param(node_id, DataType::Json, "json", IO::Input, graph);
+                add_param(node_id, DataType::String, "path", IO::Input, graph);
+                if let Ok(input) = graph[node_id].get_input("path") {
+                    graph[input].value = ValueType::String("$".to_string());
+                }
+                add_param(node_id, data.clone(), "out", IO::Output, graph);
+            }
+            Template::MergeJson => {
+                add_param(node_id, DataType::Json, "base", IO::Input, graph);
+                add_param(node_id, DataType::Json, "overlay", IO::Input, graph);
+                add_param(node_id, DataType::Json, "out", IO::Output, graph);
+            }
+            Template::PatchJson => {
+                add_param(node_id, DataType::Json, "json", IO::Input, graph);
+                add_param(node_id, DataType::Json, "patch", IO::Input, graph);
+                add_param(node_id, DataType::Json, "out", IO::Output, graph);
+            }
+            Template::SetJson => {
+                add_param(node_id, DataType::Json, "json", IO::Input, graph);
+                add_param(node_id, DataType::String, "path", IO::Input, graph);
+                add_param(node_id, DataType::Json, "value", IO::Input, graph);
+                add_param(node_id, DataType::Json, "out", IO::Output, graph);
+            }
+
+            Template::Convert(from, to) => {
+                add_param(node_id, from.clone(), "value", IO::Input, graph);
+                add_param(node_id, to.clone(), "out", IO::Output, graph);
+            }
+            Template::JsonToBinary => {
+                add_param(node_id, DataType::Json, "json", IO::Input, graph);
+                add_param(node_id, DataType::Binary, "binary", IO::Output, graph);
+            }
+            Template::BinaryToJson => {
+                add_param(node_id, DataType::Binary, "binary", IO::Input, graph);
+                add_param(node_id, DataType::Json, "json", IO::Output, graph);
+            }
+            Template::StringToBinary => {
+                add_param(node_id, DataType::String, "string", IO::Input, graph);
+                add_param(node_id, DataType::Binary, "binary", IO::Output, graph);
+            }
+            Template::BinaryToString => {
+                add_param(node_id, DataType::Binary, "binary", IO::Input, graph);
+                add_param(node_id, DataType::String, "string", IO::Output, graph);
+            }
+            Template::HexToBinary => {
+                add_param(node_id, DataType::String, "hex", IO::Input, graph);
+                add_param(node_id, DataType::Binary, "binary", IO::Output, graph);
+            }
+            Template::BinaryToHex => {
+                add_param(node_id, DataType::Binary, "binary", IO::Input, graph);
+                add_param(node_id, DataType::String, "hex", IO::Output, graph);
+            }
 
             Template::Account => {
                 add_param(node_id, DataType::String, "mnemonic", IO::Input, graph);
@@ -199,21 +534,48 @@ impl NodeTemplateTrait for Template {
                 add_param(node_id, DataType::String, "file", IO::Input, graph);
                 // TODO: replace with Account
                 add_param(node_id, DataType::Json, "account", IO::Input, graph);
+                // The chain's upload limit in bytes
+                add_param(node_id, DataType::Number, "max_size", IO::Input, graph);
+                if let Ok(input) = graph[node_id].get_input("max_size") {
+                    graph[input].value = ValueType::Number(Number::from(DEFAULT_MAX_WASM_SIZE));
+                }
                 add_param(node_id, DataType::Number, "id", IO::Output, graph);
+                // Id and code hash, what deployment outputs record
+                add_param(node_id, DataType::Json, "code", IO::Output, graph);
+                add_fee_params(node_id, graph);
             }
             Template::Instantiate => {
                 add_param(node_id, DataType::Number, "id", IO::Input, graph);
-                add_param(node_id, DataType::Json, "msg", IO::Input, graph);
+                match graph[node_id].user_data.variant.clone() {
+                    // Prefixed so they can't clash with the other inputs
+                    Some(variant) => {
+                        for (name, data) in variant.fields {
+                            add_param(node_id, data, &format!("msg.{}", name), IO::Input, graph);
+                        }
+                    }
+                    None => add_param(node_id, DataType::Json, "msg", IO::Input, graph),
+                }
                 add_param(node_id, DataType::String, "label", IO::Input, graph);
                 // TODO: replace with account
                 add_param(node_id, DataType::Json, "account", IO::Input, graph);
+                add_funds_param(node_id, graph);
 
                 // TODO: make contract type
                 add_param(node_id, DataType::Json, "contract", IO::Output, graph);
+                add_fee_params(node_id, graph);
             }
             Template::ConstructMsg => {
-                add_param(node_id, DataType::String, "type", IO::Input, graph);
-                add_param(node_id, DataType::Json, "json", IO::Input, graph);
+                match graph[node_id].user_data.variant.clone() {
+                    Some(variant) => {
+                        for (name, data) in variant.fields {
+                            add_param(node_id, data, &name, IO::Input, graph);
+                        }
+                    }
+                    None => {
+                        add_param(node_id, DataType::String, "type", IO::Input, graph);
+                        add_param(node_id, DataType::Json, "json", IO::Input, graph);
+                    }
+                }
                 add_param(node_id, DataType::Json, "msg", IO::Output, graph);
             }
             Template::DeconstructMsg => {
@@ -227,8 +589,10 @@ impl NodeTemplateTrait for Template {
                 add_param(node_id, DataType::Json, "contract", IO::Input, graph);
                 add_param(node_id, DataType::Json, "account", IO::Input, graph);
                 add_param(node_id, DataType::Json, "msg", IO::Input, graph);
+                add_funds_param(node_id, graph);
 
                 add_param(node_id, DataType::Json, "response", IO::Output, graph);
+                add_fee_params(node_id, graph);
             }
             Template::QueryContract => {
                 // TODO: replace contract
@@ -238,24 +602,98 @@ impl NodeTemplateTrait for Template {
 
                 add_param(node_id, DataType::Json, "response", IO::Output, graph);
             }
+            Template::ContractInterface => {
+                add_param(node_id, DataType::Json, "schema", IO::Output, graph);
+            }
+            Template::ContractCall(_, variant) => {
+                let execute = variant.kind == MsgKind::Execute;
+
+                // TODO: replace contract and account with their relevant types
+                add_param(node_id, DataType::Json, "contract", IO::Input, graph);
+                if execute {
+                    add_param(node_id, DataType::Json, "account", IO::Input, graph);
+                }
+                for (name, data) in variant.fields.iter() {
+                    let name = format!("msg.{}", name);
+                    add_param(node_id, data.clone(), &name, IO::Input, graph);
+                }
+                if execute {
+                    add_funds_param(node_id, graph);
+                }
+
+                add_param(node_id, DataType::Json, "response", IO::Output, graph);
+                for (name, data) in variant.response.iter() {
+                    let name = format!("response.{}", name);
+                    add_param(node_id, data.clone(), &name, IO::Output, graph);
+                }
+                if execute {
+                    add_fee_params(node_id, graph);
+                }
+            }
+
+            Template::Subgraph(subgraph) => {
+                for port in subgraph.inputs.iter() {
+                    add_param(node_id, port.data.clone(), &port.name, IO::Input, graph);
+                }
+                for port in subgraph.outputs.iter() {
+                    add_param(node_id, port.data.clone(), &port.name, IO::Output, graph);
+                }
+            }
+
+            Template::DeploymentOutput => {
+                add_param(node_id, DataType::String, "file", IO::Input, graph);
+                if let Ok(input) = graph[node_id].get_input("file") {
+                    graph[input].value = ValueType::String("deployment.json".to_string());
+                }
+                // Stored codes and contracts are added as named params
+                add_param(node_id, DataType::Json, "manifest", IO::Output, graph);
+            }
+            Template::WasmChecksum => {
+                add_param(node_id, DataType::String, "file", IO::Input, graph);
+
+                add_param(node_id, DataType::String, "hash", IO::Output, graph);
+                add_param(node_id, DataType::Number, "size", IO::Output, graph);
+                add_param(node_id, DataType::Bool, "gzipped", IO::Output, graph);
+            }
         }
     }
 }
 
-// Helper
-pub struct TemplateIterator;
+// Helper, holds the loaded contract interfaces and subgraphs to list them
+pub struct TemplateIterator {
+    pub interfaces: Rc<Vec<ContractSchema>>,
+    pub subgraphs: Rc<Vec<Subgraph>>,
+}
 impl NodeTemplateIter for TemplateIterator {
     type Item = Template;
 
     fn all_kinds(&self) -> Vec<Self::Item> {
-        vec![
+        let mut kinds = vec![
             Template::MakeBool,
             Template::MakeNumber,
             Template::MakeString,
+            Template::MakeCoin,
+            Template::FormatString,
             Template::ConstructArray(DataType::Bool),
             Template::DeconstructArray(DataType::Bool),
+            Template::ArrayLength(DataType::Json),
+            Template::ArrayGet(DataType::Json),
+            Template::ArrayConcat(DataType::Json),
+            Template::ArraySlice(DataType::Json),
+            Template::ForEach(DataType::Json, DataType::Json),
             Template::ConstructJson,
             Template::DeconstructJson,
+            Template::Extract(DataType::Json),
+            Template::MergeJson,
+            Template::PatchJson,
+            Template::SetJson,
+            Template::Convert(DataType::String, DataType::Number),
+            Template::JsonToBinary,
+            Template::BinaryToJson,
+            Template::StringToBinary,
+            Template::BinaryToString,
+            Template::HexToBinary,
+            Template::BinaryToHex,
             Template::Account,
             Template::Store,
             Template::Instantiate,
@@ -263,6 +701,34 @@ impl NodeTemplateIter for TemplateIterator {
             Template::DeconstructMsg,
             Template::ExecuteContract,
             Template::QueryContract,
-        ]
+            Template::ContractInterface,
+            Template::DeploymentOutput,
+            Template::WasmChecksum,
+        ];
+
+        kinds.extend(MathOp::ALL.into_iter().map(Template::Math));
+        kinds.extend(CompareOp::ALL.into_iter().map(Template::Compare));
+        kinds.extend([
+            Template::And,
+            Template::Or,
+            Template::Not,
+            Template::Select(DataType::Json),
+        ]);
+
+        for schema in self.interfaces.iter() {
+            for variant in schema.variants.iter() {
+                if variant.kind != MsgKind::Instantiate {
+                    kinds.push(Template::ContractCall(schema.name.clone(), variant.clone()));
+                }
+            }
+        }
+
+        kinds.extend(
+            self.subgraphs
+                .iter()
+                .map(|subgraph| Template::Subgraph(Box::new(subgraph.clone()))),
+        );
+
+        kinds
     }
 }
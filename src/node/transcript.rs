@@ -0,0 +1,238 @@
+use crate::node::data::ValueType;
+use serde_json::{json, Map, Value};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Exported as both `.json` and `.md`, suffixed with the run's start time
+const FILE_NAME: &str = "transcript";
+
+/// Ordered record of every node evaluated during a run
+pub struct Transcript {
+    /// Unix timestamp of when the run started
+    pub started: u64,
+    /// Txs were only simulated, not broadcast
+    pub dry_run: bool,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+pub struct TranscriptEntry {
+    pub node: String,
+    pub inputs: Vec<(String, Option<ValueType>)>,
+    pub outputs: Vec<(String, ValueType)>,
+    /// Hash and gas of the tx the node broadcast or simulated
+    pub tx: Option<Tx>,
+    pub duration: Duration,
+    pub status: Status,
+}
+
+pub struct Tx {
+    pub hash: String,
+    pub gas_used: u64,
+}
+
+impl Tx {
+    /// Read from the `tx` and `gas_used` outputs of the effectful nodes
+    pub fn from_outputs(outputs: &[(String, ValueType)]) -> Option<Self> {
+        let output = |name: &str| {
+            outputs
+                .iter()
+                .find(|(output, _)| output == name)
+                .map(|(_, value)| to_json(value))
+        };
+        Some(Self {
+            hash: output("tx")?["hash"].as_str()?.to_string(),
+            gas_used: output("gas_used")?.as_u64()?,
+        })
+    }
+}
+
+pub enum Status {
+    Evaluated,
+    Skipped,
+    Failed(String),
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Self {
+            started: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+            dry_run: false,
+            entries: vec![],
+        }
+    }
+}
+
+fn to_json(value: &ValueType) -> Value {
+    value.clone().try_into().unwrap_or_default()
+}
+
+impl Transcript {
+    pub fn to_json(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let inputs: Map<String, Value> = entry
+                    .inputs
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.as_ref().map(to_json).into()))
+                    .collect();
+                let outputs: Map<String, Value> = entry
+                    .outputs
+                    .iter()
+                    .map(|(name, value)| (name.clone(), to_json(value)))
+                    .collect();
+                let (status, error) = match &entry.status {
+                    Status::Evaluated => ("evaluated", None),
+                    Status::Skipped => ("skipped", None),
+                    Status::Failed(err) => ("failed", Some(err.clone())),
+                };
+
+                let tx = entry.tx.as_ref().map(|tx| {
+                    json!({
+                        "hash": tx.hash,
+                        "gas_used": tx.gas_used,
+                    })
+                });
+
+                json!({
+                    "node": entry.node,
+                    "status": status,
+                    "error": error,
+                    "duration_ms": entry.duration.as_millis() as u64,
+                    "tx": tx,
+                    "inputs": inputs,
+                    "outputs": outputs,
+                })
+            })
+            .collect();
+
+        json!({
+            "started": self.started,
+            "dry_run": self.dry_run,
+            "entries": entries,
+        })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Execution transcript\n");
+        let _ = writeln!(md, "Started at unix time {}\n", self.started);
+        if self.dry_run {
+            let _ = writeln!(md, "Dry run, no txs were broadcast\n");
+        }
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let status = match &entry.status {
+                Status::Evaluated => "evaluated".to_string(),
+                Status::Skipped => "skipped".to_string(),
+                Status::Failed(err) => format!("failed: {}", err),
+            };
+            let _ = writeln!(md, "## {}. {}\n", i + 1, entry.node);
+            let _ = writeln!(
+                md,
+                "Status: {} in {}ms\n",
+                status,
+                entry.duration.as_millis()
+            );
+            if let Some(tx) = &entry.tx {
+                let _ = writeln!(md, "Tx `{}` used {} gas\n", tx.hash, tx.gas_used);
+            }
+
+            if !entry.inputs.is_empty() {
+                let _ = writeln!(md, "| Input | Value |\n| --- | --- |");
+                for (name, value) in entry.inputs.iter() {
+                    let value = value
+                        .as_ref()
+                        .map(|value| to_json(value).to_string())
+                        .unwrap_or_else(|| "not evaluated".to_string());
+                    let _ = writeln!(md, "| {} | `{}` |", name, value);
+                }
+                let _ = writeln!(md);
+            }
+
+            if !entry.outputs.is_empty() {
+                let _ = writeln!(md, "| Output | Value |\n| --- | --- |");
+                for (name, value) in entry.outputs.iter() {
+                    let _ = writeln!(md, "| {} | `{}` |", name, to_json(value));
+                }
+                let _ = writeln!(md);
+            }
+        }
+
+        md
+    }
+
+    /// Each run gets its own files so older exports aren't overwritten
+    pub fn file_name(&self) -> String {
+        format!("{}-{}", FILE_NAME, self.started)
+    }
+
+    /// Writes the transcript as json and markdown next to each other
+    pub fn export(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(
+            path.with_extension("json"),
+            serde_json::to_string_pretty(&self.to_json())?,
+        )?;
+        std::fs::write(path.with_extension("md"), self.to_markdown())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Number;
+
+    fn entry(outputs: Vec<(String, ValueType)>) -> TranscriptEntry {
+        TranscriptEntry {
+            node: "Instantiate".to_string(),
+            inputs: vec![],
+            tx: Tx::from_outputs(&outputs),
+            outputs,
+            duration: Duration::ZERO,
+            status: Status::Evaluated,
+        }
+    }
+
+    #[test]
+    fn txs_are_recorded_with_their_gas() {
+        let outputs = vec![
+            (
+                "gas_used".to_string(),
+                ValueType::Number(Number::from(61_000)),
+            ),
+            (
+                "tx".to_string(),
+                ValueType::Json(json!({ "body": {}, "signature": "ab", "hash": "ABC123" })),
+            ),
+        ];
+        let transcript = Transcript {
+            started: 0,
+            dry_run: true,
+            entries: vec![entry(outputs)],
+        };
+
+        assert_eq!(
+            transcript.to_json()["entries"][0]["tx"],
+            json!({ "hash": "ABC123", "gas_used": 61_000 })
+        );
+        assert!(transcript
+            .to_markdown()
+            .contains("Tx `ABC123` used 61000 gas"));
+    }
+
+    #[test]
+    fn reused_or_other_nodes_have_no_tx() {
+        let reused = vec![
+            ("gas_used".to_string(), ValueType::Null),
+            ("tx".to_string(), ValueType::Null),
+        ];
+        assert!(entry(reused).tx.is_none());
+        assert!(entry(vec![]).tx.is_none());
+    }
+}
@@ -0,0 +1,435 @@
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const WASM_MAGIC: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+const WASM_VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
+/// Default wasmd upload limit, chains can configure their own
+pub const DEFAULT_MAX_WASM_SIZE: usize = 800 * 1024;
+
+const TYPE_SECTION: u8 = 1;
+const EXPORT_SECTION: u8 = 7;
+const CODE_SECTION: u8 = 10;
+const F32: u8 = 0x7d;
+const F64: u8 = 0x7c;
+
+/// Exports the contract needs, any of the names satisfy the requirement
+/// since older secret contracts still use init and handle
+const REQUIRED_EXPORTS: [&[&str]; 5] = [
+    &["instantiate", "init"],
+    &["execute", "handle"],
+    &["query"],
+    &["allocate"],
+    &["deallocate"],
+];
+/// Prefixes of the markers the VM uses to check which interface the contract targets
+const INTERFACE_MARKERS: [&str; 2] = ["interface_version_", "cosmwasm_vm_version_"];
+
+/// A contract build as found on disk
+pub struct WasmFile {
+    /// Uncompressed wasm
+    pub wasm: Vec<u8>,
+    /// File size, compressed if gzipped
+    pub size: usize,
+    pub gzipped: bool,
+    /// Hex encoded SHA-256 of the uncompressed wasm, the same hash the chain reports as code hash
+    pub code_hash: String,
+}
+
+impl WasmFile {
+    /// Gzipped files are decompressed up to `max_size`, anything larger is rejected
+    pub fn read(path: &Path, max_size: usize) -> anyhow::Result<Self> {
+        let file = std::fs::read(path)
+            .map_err(|err| anyhow!("Could not read {}: {}", path.display(), err))?;
+        Self::from_bytes(file, max_size).map_err(|err| anyhow!("{}: {}", path.display(), err))
+    }
+
+    fn from_bytes(file: Vec<u8>, max_size: usize) -> anyhow::Result<Self> {
+        let size = file.len();
+        let gzipped = file.starts_with(&GZIP_MAGIC);
+
+        let wasm = if gzipped {
+            // Bounded so a tiny archive can't inflate into gigabytes
+            let mut wasm = vec![];
+            GzDecoder::new(file.as_slice())
+                .take(max_size as u64 + 1)
+                .read_to_end(&mut wasm)
+                .map_err(|err| anyhow!("Could not decompress: {}", err))?;
+            if wasm.len() > max_size {
+                anyhow::bail!("Decompresses to more than the limit of {} bytes", max_size);
+            }
+            wasm
+        } else {
+            file
+        };
+
+        let code_hash = Sha256::digest(&wasm)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        Ok(Self {
+            wasm,
+            size,
+            gzipped,
+            code_hash,
+        })
+    }
+}
+
+impl WasmFile {
+    /// Catches broken builds before spending gas on storing them.
+    /// The chain's size limit applies to both the uploaded and uncompressed code.
+    ///
+    /// Float detection only looks at function signatures and locals,
+    /// floats used purely inside instructions aren't caught.
+    pub fn validate(&self, max_size: usize) -> anyhow::Result<()> {
+        if self.size > max_size || self.wasm.len() > max_size {
+            anyhow::bail!(
+                "Wasm is {} bytes, the limit is {}",
+                self.size.max(self.wasm.len()),
+                max_size
+            );
+        }
+
+        if !self.wasm.starts_with(&WASM_MAGIC) {
+            anyhow::bail!("File is not wasm, missing magic bytes");
+        }
+        if self.wasm.get(4..8) != Some(&WASM_VERSION[..]) {
+            anyhow::bail!("Unsupported wasm version");
+        }
+
+        let mut exports = vec![];
+        let mut floats = false;
+
+        let mut reader = Reader::new(&self.wasm[8..]);
+        while !reader.is_empty() {
+            let id = reader.byte()?;
+            let size = reader.leb()? as usize;
+            let mut section = Reader::new(reader.bytes(size)?);
+
+            match id {
+                TYPE_SECTION => {
+                    for _ in 0..section.leb()? {
+                        // Function type marker
+                        section.byte()?;
+                        for _ in 0..2 {
+                            // Params then results
+                            let count = section.leb()? as usize;
+                            floats |= section.bytes(count)?.iter().any(|t| *t == F32 || *t == F64);
+                        }
+                    }
+                }
+                EXPORT_SECTION => {
+                    for _ in 0..section.leb()? {
+                        let len = section.leb()? as usize;
+                        exports.push(String::from_utf8_lossy(section.bytes(len)?).to_string());
+                        // Export kind and index
+                        section.byte()?;
+                        section.leb()?;
+                    }
+                }
+                CODE_SECTION => {
+                    for _ in 0..section.leb()? {
+                        let len = section.leb()? as usize;
+                        let mut body = Reader::new(section.bytes(len)?);
+                        for _ in 0..body.leb()? {
+                            body.leb()?;
+                            let t = body.byte()?;
+                            floats |= t == F32 || t == F64;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for names in REQUIRED_EXPORTS {
+            if !names
+                .iter()
+                .any(|name| exports.iter().any(|export| export == name))
+            {
+                anyhow::bail!("Missing required export {}", names.join(" or "));
+            }
+        }
+
+        if !exports.iter().any(|export| {
+            INTERFACE_MARKERS
+                .iter()
+                .any(|marker| export.starts_with(marker))
+        }) {
+            anyhow::bail!("Missing the interface version marker export");
+        }
+
+        if floats {
+            anyhow::bail!("Contract uses floating point types which the chain rejects");
+        }
+
+        Ok(())
+    }
+}
+
+/// Wasm files already read, kept until they change on disk or are read with another size limit
+/// so evaluating every frame stays cheap
+#[derive(Default)]
+pub struct WasmCache {
+    files: RefCell<HashMap<PathBuf, Cached>>,
+}
+
+struct Cached {
+    modified: SystemTime,
+    max_size: usize,
+    file: Rc<WasmFile>,
+}
+
+impl WasmCache {
+    pub fn read(&self, path: &Path, max_size: usize) -> anyhow::Result<Rc<WasmFile>> {
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| anyhow!("Could not read {}: {}", path.display(), err))?;
+
+        if let Some(cached) = self.files.borrow().get(path) {
+            if cached.modified == modified && cached.max_size == max_size {
+                return Ok(cached.file.clone());
+            }
+        }
+
+        let file = Rc::new(WasmFile::read(path, max_size)?);
+        self.files.borrow_mut().insert(
+            path.to_path_buf(),
+            Cached {
+                modified,
+                max_size,
+                file: file.clone(),
+            },
+        );
+        Ok(file)
+    }
+}
+
+/// Bare minimum to walk through the wasm sections
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            anyhow::bail!("Wasm is truncated");
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    /// Unsigned LEB128
+    fn leb(&mut self) -> anyhow::Result<u32> {
+        let mut result = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            result |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        anyhow::bail!("Invalid LEB128 integer")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const EXPORTS: [&str; 6] = [
+        "instantiate",
+        "execute",
+        "query",
+        "allocate",
+        "deallocate",
+        "interface_version_8",
+    ];
+
+    fn leb(mut value: usize) -> Vec<u8> {
+        let mut bytes = vec![];
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    fn section(id: u8, content: Vec<u8>) -> Vec<u8> {
+        [vec![id], leb(content.len()), content].concat()
+    }
+
+    fn exports(names: &[&str]) -> Vec<u8> {
+        let mut content = leb(names.len());
+        for name in names {
+            content.extend(leb(name.len()));
+            content.extend(name.as_bytes());
+            // Function 0
+            content.extend([0x00, 0x00]);
+        }
+        section(EXPORT_SECTION, content)
+    }
+
+    /// A single function type taking the params
+    fn types(params: &[u8]) -> Vec<u8> {
+        let content = [vec![1, 0x60], leb(params.len()), params.to_vec(), vec![0]].concat();
+        section(TYPE_SECTION, content)
+    }
+
+    /// A single function body declaring one local of each type
+    fn code(locals: &[u8]) -> Vec<u8> {
+        let mut body = leb(locals.len());
+        for local in locals {
+            body.extend([1, *local]);
+        }
+        body.push(0x0b);
+        section(CODE_SECTION, [vec![1], leb(body.len()), body].concat())
+    }
+
+    fn module(sections: &[Vec<u8>]) -> Vec<u8> {
+        [
+            WASM_MAGIC.to_vec(),
+            WASM_VERSION.to_vec(),
+            sections.concat(),
+        ]
+        .concat()
+    }
+
+    fn validate(sections: &[Vec<u8>]) -> anyhow::Result<()> {
+        WasmFile::from_bytes(module(sections), DEFAULT_MAX_WASM_SIZE)?
+            .validate(DEFAULT_MAX_WASM_SIZE)
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn leb_reads_multi_byte_integers() {
+        let mut reader = Reader::new(&[0xe5, 0x8e, 0x26, 0x7f]);
+        assert_eq!(reader.leb().unwrap(), 624485);
+        assert_eq!(reader.leb().unwrap(), 127);
+        assert!(reader.is_empty());
+
+        assert!(Reader::new(&[0x80, 0x80]).leb().is_err());
+        assert!(Reader::new(&[0xff; 6]).leb().is_err());
+    }
+
+    #[test]
+    fn valid_contract_passes() {
+        let valid = [types(&[0x7f]), exports(&EXPORTS), code(&[0x7e])];
+        validate(&valid).unwrap();
+    }
+
+    #[test]
+    fn bad_magic_or_version_fails() {
+        let mut wasm = module(&[exports(&EXPORTS)]);
+        wasm[0] = 0xff;
+        let file = WasmFile::from_bytes(wasm.clone(), DEFAULT_MAX_WASM_SIZE).unwrap();
+        assert!(file.validate(DEFAULT_MAX_WASM_SIZE).is_err());
+
+        wasm[0] = 0x00;
+        wasm[4] = 0x02;
+        let file = WasmFile::from_bytes(wasm, DEFAULT_MAX_WASM_SIZE).unwrap();
+        let err = file.validate(DEFAULT_MAX_WASM_SIZE).unwrap_err();
+        assert!(err.to_string().contains("version"));
+    }
+
+    #[test]
+    fn exports_can_use_the_old_names() {
+        let mut names = EXPORTS;
+        names[0] = "init";
+        names[1] = "handle";
+        validate(&[exports(&names)]).unwrap();
+
+        let err = validate(&[exports(&EXPORTS[1..])]).unwrap_err();
+        assert!(err.to_string().contains("instantiate or init"));
+        let err = validate(&[exports(&["instantiate", "execute", "allocate"])]).unwrap_err();
+        assert!(err.to_string().contains("query"));
+    }
+
+    #[test]
+    fn interface_marker_is_required() {
+        assert!(validate(&[exports(&EXPORTS[..5])]).is_err());
+
+        let mut names = EXPORTS;
+        names[5] = "cosmwasm_vm_version_4";
+        validate(&[exports(&names)]).unwrap();
+    }
+
+    #[test]
+    fn floats_in_signatures_or_locals_fail() {
+        assert!(validate(&[types(&[0x7f, F32]), exports(&EXPORTS)]).is_err());
+        assert!(validate(&[exports(&EXPORTS), code(&[0x7f, F64])]).is_err());
+    }
+
+    #[test]
+    fn truncated_wasm_fails() {
+        let mut wasm = module(&[exports(&EXPORTS)]);
+        wasm.truncate(wasm.len() - 3);
+        let file = WasmFile::from_bytes(wasm, DEFAULT_MAX_WASM_SIZE).unwrap();
+        let err = file.validate(DEFAULT_MAX_WASM_SIZE).unwrap_err();
+        assert!(err.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn size_limit_applies() {
+        let wasm = module(&[exports(&EXPORTS)]);
+        let file = WasmFile::from_bytes(wasm.clone(), DEFAULT_MAX_WASM_SIZE).unwrap();
+        assert!(file.validate(wasm.len() - 1).is_err());
+        file.validate(wasm.len()).unwrap();
+    }
+
+    #[test]
+    fn gzipped_wasm_hashes_like_the_raw_one() {
+        let wasm = module(&[exports(&EXPORTS)]);
+        let raw = WasmFile::from_bytes(wasm.clone(), DEFAULT_MAX_WASM_SIZE).unwrap();
+        let gzipped = WasmFile::from_bytes(gzip(&wasm), DEFAULT_MAX_WASM_SIZE).unwrap();
+
+        assert!(!raw.gzipped && gzipped.gzipped);
+        assert_eq!(raw.code_hash, gzipped.code_hash);
+        assert_eq!(raw.code_hash, hex::encode(Sha256::digest(&wasm)));
+        assert_eq!(raw.size, wasm.len());
+        assert_ne!(gzipped.size, wasm.len());
+        assert_eq!(gzipped.wasm, wasm);
+    }
+
+    #[test]
+    fn decompression_is_bounded() {
+        let bomb = gzip(&vec![0; 1024 * 1024]);
+        let err = WasmFile::from_bytes(bomb.clone(), 1024).err().unwrap();
+        assert!(err.to_string().contains("1024"));
+        assert!(WasmFile::from_bytes(bomb, 1024 * 1024).is_ok());
+    }
+}